@@ -0,0 +1,105 @@
+//! Parser and writer throughput on a medium sized flattened design.
+//!
+//! Run with `cargo bench`, criterion reports the throughput in bytes of RTLIL per second.
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+
+const COUNTERS: &str = include_str!("fixtures/counters.il");
+
+/// The system allocator, counting allocations to compare the owned and borrowed parsers
+struct Counting;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+/// The number of allocations made by `f`
+fn allocations<T>(f: impl FnOnce() -> T) -> usize {
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    let result = f();
+    let count = ALLOCATIONS.load(Ordering::Relaxed) - before;
+    drop(result);
+    count
+}
+
+fn parse(c: &mut Criterion) {
+    let mut group = c.benchmark_group("parse");
+    group.throughput(Throughput::Bytes(COUNTERS.len() as u64));
+    group.bench_function("counters", |b| {
+        b.iter(|| rtlicious::parse(std::hint::black_box(COUNTERS)).unwrap())
+    });
+    group.finish();
+}
+
+fn round_trip(c: &mut Criterion) {
+    let design = rtlicious::parse(COUNTERS).unwrap();
+    let written = design.to_rtlil_string();
+    let mut group = c.benchmark_group("round_trip");
+    group.throughput(Throughput::Bytes(written.len() as u64));
+    group.bench_function("write", |b| {
+        b.iter(|| std::hint::black_box(&design).to_rtlil_string())
+    });
+    group.bench_function("write_parse", |b| {
+        b.iter(|| rtlicious::parse(std::hint::black_box(&design).to_rtlil_string()).unwrap())
+    });
+    group.finish();
+}
+
+/// A connection from a 4096 member concatenation, as found in flattened buses
+fn concat(c: &mut Criterion) {
+    let members: Vec<String> = (0..4096).rev().map(|i| format!("\\a [{}]", i)).collect();
+    let src = format!(
+        "module \\m\n  wire width 4096 \\a\n  wire width 4096 \\b\n  connect \\b {{ {} }}\nend\n",
+        members.join(" ")
+    );
+    let design = rtlicious::parse(&src).unwrap();
+    let module = &design.modules()["m"];
+    let (_, concat) = &module.connections()[0];
+    let mut group = c.benchmark_group("concat");
+    group.throughput(Throughput::Bytes(src.len() as u64));
+    group.bench_function("parse", |b| {
+        b.iter(|| rtlicious::parse(std::hint::black_box(&src)).unwrap())
+    });
+    group.bench_function("width", |b| {
+        b.iter(|| module.sigspec_width(std::hint::black_box(concat)).unwrap())
+    });
+    group.finish();
+}
+
+/// The borrowed scan against the owned parser, their allocation counts are printed first
+fn borrowed(c: &mut Criterion) {
+    eprintln!(
+        "allocations: parse {}, parse_borrowed {}",
+        allocations(|| rtlicious::parse(COUNTERS).unwrap()),
+        allocations(|| rtlicious::parse_borrowed(COUNTERS).unwrap())
+    );
+    let mut group = c.benchmark_group("borrowed");
+    group.throughput(Throughput::Bytes(COUNTERS.len() as u64));
+    group.bench_function("parse", |b| {
+        b.iter(|| rtlicious::parse(std::hint::black_box(COUNTERS)).unwrap())
+    });
+    group.bench_function("parse_borrowed", |b| {
+        b.iter(|| rtlicious::parse_borrowed(std::hint::black_box(COUNTERS)).unwrap())
+    });
+    group.finish();
+}
+
+criterion_group!(benches, parse, round_trip, concat, borrowed);
+criterion_main!(benches);
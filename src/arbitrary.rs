@@ -0,0 +1,341 @@
+//! `proptest` strategies generating valid designs, used to check that the writer and the parser
+//! agree with each other.
+//!
+//! The generated values respect what the text format can represent:
+//! * wires are at least 1 bit wide and only ports carry a port id
+//! * value bits are lowercase `0 1 x z m -`
+//! * ranges apply to wire ids only
+//! * a case compares against at least one signal, or none at all (the default case)
+//! * integers parsed as `i32` (widths, offsets, indices) stay positive
+
+use std::collections::HashMap;
+
+use proptest::{collection::hash_map, collection::vec, option, prelude::*};
+
+use crate::*;
+
+/// Public or auto-generated names, without their sigil
+fn name() -> impl Strategy<Value = String> {
+    "[a-zA-Z_][a-zA-Z0-9_.$]{0,6}"
+}
+
+fn attributes() -> impl Strategy<Value = HashMap<String, Constant>> {
+    hash_map(name(), any::<Constant>(), 0..3)
+}
+
+fn small() -> impl Strategy<Value = usize> {
+    0..64usize
+}
+
+impl Arbitrary for Constant {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        prop_oneof![
+            vec(
+                prop::sample::select(vec!['0', '1', 'x', 'z', 'm', '-']),
+                0..8
+            )
+            .prop_map(Constant::Value),
+            any::<i32>().prop_map(Constant::Integer),
+            any::<String>().prop_map(Constant::String),
+            "-?[0-9]{1,3}\\.[0-9]{1,3}(e-?[0-9])?".prop_map(Constant::Real),
+        ]
+        .boxed()
+    }
+}
+
+impl Arbitrary for SigSpec {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        let leaf = prop_oneof![
+            any::<Constant>().prop_map(SigSpec::Constant),
+            name().prop_map(SigSpec::WireId),
+            (name(), small(), option::of(small())).prop_map(|(id, start, end)| {
+                SigSpec::Range(Box::new(SigSpec::WireId(id)), start, end)
+            }),
+        ];
+        leaf.prop_recursive(3, 16, 4, |inner| vec(inner, 0..4).prop_map(SigSpec::Concat))
+            .boxed()
+    }
+}
+
+impl Arbitrary for Wire {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        (
+            1..64usize,
+            small(),
+            any::<(bool, bool, bool, bool, bool)>(),
+            small(),
+            attributes(),
+        )
+            .prop_map(
+                |(width, offset, (input, output, inout, upto, signed), port_id, attributes)| Wire {
+                    width,
+                    offset,
+                    input,
+                    output,
+                    inout,
+                    upto,
+                    signed,
+                    port_id: (input || output || inout).then_some(port_id),
+                    attributes,
+                },
+            )
+            .boxed()
+    }
+}
+
+impl Arbitrary for Memory {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        (small(), small(), small(), attributes())
+            .prop_map(|(width, size, offset, attributes)| Memory {
+                width,
+                size,
+                offset,
+                attributes,
+            })
+            .boxed()
+    }
+}
+
+fn parameter_kind() -> impl Strategy<Value = ParameterKind> {
+    prop_oneof![Just(ParameterKind::Signed), Just(ParameterKind::Real)]
+}
+
+impl Arbitrary for Cell {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        (
+            attributes(),
+            name(),
+            hash_map(
+                name(),
+                (any::<Constant>(), option::of(parameter_kind())),
+                0..3,
+            ),
+            hash_map(name(), any::<SigSpec>(), 0..3),
+        )
+            .prop_map(|(attributes, cell_type, parameters, connections)| Cell {
+                attributes,
+                cell_type,
+                parameter_kinds: parameters
+                    .iter()
+                    .filter_map(|(name, (_, kind))| Some((name.clone(), (*kind)?)))
+                    .collect(),
+                parameters: parameters
+                    .into_iter()
+                    .map(|(name, (value, _))| (name, value))
+                    .collect(),
+                connections,
+            })
+            .boxed()
+    }
+}
+
+fn assignment() -> impl Strategy<Value = (SigSpec, SigSpec)> {
+    (any::<SigSpec>(), any::<SigSpec>())
+}
+
+impl Arbitrary for Switch {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        let case = |body: BoxedStrategy<CaseBody>| {
+            (
+                attributes(),
+                option::of(vec(any::<SigSpec>(), 1..3)),
+                vec(body, 0..3),
+            )
+                .prop_map(|(attributes, compare_against, case_bodies)| Case {
+                    attributes,
+                    compare_against,
+                    case_bodies,
+                })
+        };
+        let switch = |case| {
+            (attributes(), any::<SigSpec>(), vec(case, 0..3)).prop_map(
+                |(attributes, switch_on_sigspec, cases)| Switch {
+                    attributes,
+                    switch_on_sigspec,
+                    cases,
+                },
+            )
+        };
+        let leaf = switch(case(assignment().prop_map(CaseBody::Assign).boxed()));
+        leaf.prop_recursive(2, 8, 3, move |inner| {
+            let body = prop_oneof![
+                inner.prop_map(CaseBody::Switch),
+                assignment().prop_map(CaseBody::Assign),
+            ];
+            switch(case(body.boxed()))
+        })
+        .boxed()
+    }
+}
+
+impl Arbitrary for SyncOn {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        let signal_sync = prop_oneof![
+            Just(SignalSync::Low),
+            Just(SignalSync::High),
+            Just(SignalSync::Posedge),
+            Just(SignalSync::Negedge),
+            Just(SignalSync::Edge),
+        ];
+        prop_oneof![
+            Just(SyncOn::Global),
+            Just(SyncOn::Init),
+            Just(SyncOn::Always),
+            (signal_sync, any::<SigSpec>()).prop_map(|(sync, on)| SyncOn::Signal(sync, on)),
+        ]
+        .boxed()
+    }
+}
+
+impl Arbitrary for Sync {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        let memwr = (attributes(), any::<(SigSpec, SigSpec, SigSpec, SigSpec)>()).prop_map(
+            |(attributes, (address, data, enable, priority_mask))| Memwr {
+                attributes,
+                address,
+                data,
+                enable,
+                priority_mask,
+            },
+        );
+        (
+            any::<SyncOn>(),
+            vec((attributes(), assignment()), 0..3),
+            hash_map(name(), memwr, 0..2),
+        )
+            .prop_map(|(sync_event, updates, memwrs)| Sync {
+                sync_event,
+                updates: updates
+                    .into_iter()
+                    .map(|(attributes, (dest, src))| Update {
+                        attributes,
+                        dest,
+                        src,
+                    })
+                    .collect(),
+                memwrs,
+            })
+            .boxed()
+    }
+}
+
+impl Arbitrary for Process {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        (
+            attributes(),
+            vec(assignment(), 0..3),
+            vec(any::<Switch>(), 0..2),
+            vec(any::<Sync>(), 0..3),
+        )
+            .prop_map(|(attributes, assignments, switches, syncs)| Process {
+                attributes,
+                assignments,
+                switches,
+                syncs,
+            })
+            .boxed()
+    }
+}
+
+impl Arbitrary for Module {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        let wires = hash_map(name(), (any::<bool>(), any::<Wire>()), 0..6).prop_map(|wires| {
+            wires
+                .into_iter()
+                .map(|(name, (public, wire))| {
+                    let id = if public {
+                        Id::Public(name)
+                    } else {
+                        Id::Autogen(name)
+                    };
+                    (id, wire)
+                })
+                .collect()
+        });
+        (
+            attributes(),
+            hash_map(name(), option::of(any::<Constant>()), 0..3),
+            wires,
+            hash_map(name(), any::<Memory>(), 0..2),
+            hash_map(name(), any::<Cell>(), 0..4),
+            hash_map(name(), any::<Process>(), 0..2),
+            vec(assignment(), 0..4),
+        )
+            .prop_map(
+                |(attributes, parameters, wires, memories, cells, processes, connections)| Module {
+                    attributes,
+                    parameters,
+                    wires,
+                    memories,
+                    cells,
+                    processes,
+                    connections,
+                    unknown_stmts: vec![],
+                    partial: false,
+                },
+            )
+            .boxed()
+    }
+}
+
+impl Arbitrary for Design {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        (
+            option::of(any::<i32>()),
+            hash_map(name(), any::<Module>(), 1..3),
+        )
+            .prop_map(|(autoidx, modules)| Design { autoidx, modules })
+            .boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(64))]
+
+        #[test]
+        fn test_round_trip(design in any::<Design>()) {
+            let written = design.to_rtlil_string();
+            let parsed = Design::new_from_str(&written)
+                .unwrap_or_else(|e| panic!("{}\n\n{}", e, written));
+            assert_eq!(parsed, design);
+        }
+    }
+}
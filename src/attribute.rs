@@ -2,10 +2,30 @@
 //! Declares an attribute with the given identifier and value.
 //! `<attr-stmt> ::= attribute <id> <constant> <eol>`
 
-use nom::{bytes::complete::tag, IResult};
+use std::collections::HashMap;
+
+use nom::bytes::complete::tag;
 use nom_tracable::tracable_parser;
 
-use crate::{characters, constant, identifier, Constant, Span};
+use crate::*;
+
+macro_rules! impl_has_attributes {
+    ($($item:ty),*) => {
+        $(
+            impl HasAttributes for $item {
+                fn attributes(&self) -> &HashMap<String, Constant> {
+                    &self.attributes
+                }
+
+                fn attributes_mut(&mut self) -> &mut HashMap<String, Constant> {
+                    &mut self.attributes
+                }
+            }
+        )*
+    };
+}
+
+impl_has_attributes!(Module, Wire, Memory, Cell, Process, Switch, Case, Memwr, Update);
 
 #[tracable_parser]
 pub(crate) fn attr_stmt(input: Span) -> IResult<Span, (String, Constant)> {
@@ -25,6 +45,66 @@ mod tests {
 
     use super::*;
 
+    #[test]
+    fn test_has_attributes() {
+        let input = indoc::indoc! {r#"
+            attribute \src "serv_top.v:3.1-658.10"
+            attribute \keep 1
+            wire \a
+        "#};
+        let span = Span::new_extra(input, Default::default());
+        let (_, (_, wire)) = wire::wire(span).unwrap();
+        assert_eq!(wire.attribute_str("\\src"), Some("serv_top.v:3.1-658.10"));
+        assert_eq!(wire.attribute_str("src"), Some("serv_top.v:3.1-658.10"));
+        assert_eq!(wire.attribute_int("\\src"), None);
+        assert_eq!(wire.attribute_int("\\keep"), Some(1));
+        assert_eq!(wire.attribute_str("\\keep"), None);
+        assert_eq!(wire.attribute("\\keep"), Some(&Constant::Integer(1)));
+        assert_eq!(wire.attribute("\\missing"), None);
+    }
+
+    #[test]
+    fn test_attribute_bool() {
+        let input = indoc::indoc! {r#"
+            attribute \keep 1
+            attribute \blackbox 0
+            attribute \init 2'10
+            attribute \dont_touch 1'x
+            attribute \src "top.v:1.1-1.2"
+            cell $not $n
+            end
+        "#};
+        let span = Span::new_extra(input, Default::default());
+        let (_, (_, cell)) = cell::cell(span).unwrap();
+        assert_eq!(cell.attribute_bool("\\keep"), Some(true));
+        assert_eq!(cell.attribute_bool("blackbox"), Some(false));
+        assert_eq!(cell.attribute_bool("init"), Some(true));
+        assert_eq!(cell.attribute_bool("dont_touch"), None);
+        assert_eq!(cell.attribute_bool("src"), None);
+        assert_eq!(cell.attribute_bool("missing"), None);
+    }
+
+    #[test]
+    fn test_set_attribute() {
+        let mut design = parse("module \\a\n  wire \\w\nend\n").unwrap();
+        let module = design.module_mut("a").unwrap();
+        let wire = module.wire_mut("w").unwrap();
+        assert_eq!(wire.set_attribute("\\keep", Constant::Integer(1)), None);
+        assert_eq!(wire.attribute_bool("keep"), Some(true));
+        assert_eq!(
+            wire.set_attribute("keep", Constant::Integer(0)),
+            Some(Constant::Integer(1))
+        );
+        wire.attributes_mut().remove("keep");
+        assert!(wire.attributes().is_empty());
+        assert!(module.cell_mut("w").is_none());
+
+        module.set_attribute("top", Constant::Integer(1));
+        assert!(design
+            .to_rtlil_string()
+            .contains("attribute \\top 1\nmodule \\a\n"));
+    }
+
     #[test]
     fn test_attr_stmt() {
         let vectors = vec![
@@ -51,4 +131,48 @@ mod tests {
             assert_eq!(ret.1, *expected, "Test case {}", i);
         }
     }
+
+    #[test]
+    fn test_attr_stmt_signed_integer() {
+        let vectors = [
+            // integers, signed or not, are not taken for the width of a value
+            (
+                "attribute \\foo -5\n",
+                ("foo".to_string(), Constant::Integer(-5)),
+            ),
+            (
+                "attribute \\foo 5\n",
+                ("foo".to_string(), Constant::Integer(5)),
+            ),
+            (
+                "attribute \\foo -2147483648\n",
+                ("foo".to_string(), Constant::Integer(i32::MIN)),
+            ),
+            (
+                "attribute \\foo 2'01\n",
+                ("foo".to_string(), Constant::Value(vec!['1', '0'])),
+            ),
+        ];
+        let info = TracableInfo::new().parser_width(64).fold("term");
+        for (i, (input, expected)) in vectors.iter().enumerate() {
+            let span = LocatedSpan::new_extra(*input, info);
+            let ret = attr_stmt(span).unwrap();
+            assert!(ret.0.is_empty(), "Test case {}", i);
+            assert_eq!(ret.1, *expected, "Test case {}", i);
+        }
+        // a value has no sign
+        let span = LocatedSpan::new_extra("attribute \\foo -2'01\n", info);
+        assert!(attr_stmt(span).is_err());
+    }
+
+    #[test]
+    fn test_attr_stmt_real() {
+        let span = Span::new_extra("attribute \\clk_period 10.0\n", Default::default());
+        let (rest, attribute) = attr_stmt(span).unwrap();
+        assert!(rest.is_empty());
+        assert_eq!(
+            attribute,
+            ("clk_period".to_string(), Constant::Real("10.0".to_string()))
+        );
+    }
 }
@@ -0,0 +1,290 @@
+//! A borrowed view of a design, for read-only passes over large inputs.
+//!
+//! Scanning only reads the lines declaring modules and their top-level items, the names it keeps
+//! are slices of the input. A module body is checked, and turned into an owned `Module`, by the
+//! full parser when asked for.
+
+use nom::Slice;
+
+use crate::*;
+
+impl<'a> DesignRef<'a> {
+    /// Read the modules of `input` and the names of their wires, memories, cells and processes
+    ///
+    /// Only the lines declaring items are looked at, a module whose body does not parse is only
+    /// reported by `ModuleRef::to_module`, unless a line opening or closing a block is broken:
+    /// the blocks are read with the statement parsers. Names are stored without sigil, as in
+    /// `Design`.
+    ///
+    /// ```
+    /// let src = "module \\m\n  wire \\a\n  cell $not $n\n    connect \\A \\a\n  end\nend\n";
+    /// let design = rtlicious::parse_borrowed(src).unwrap();
+    /// let module = design.module("m").unwrap();
+    /// assert_eq!(module.wires(), ["a"]);
+    /// assert_eq!(module.cells(), [("n", "not")]);
+    /// let parsed = rtlicious::parse(src).unwrap();
+    /// assert_eq!(module.to_module().unwrap(), parsed.modules()["m"]);
+    /// ```
+    pub fn scan(input: &'a str) -> Result<Self, ParseError> {
+        let error = |at: usize| {
+            ParseError::new(
+                input,
+                Span::new_extra(input, Default::default()).slice(at..),
+            )
+        };
+        let mut autoidx = None;
+        let mut modules = vec![];
+        // the module being read, where its text starts and how many blocks are open in it
+        let mut open: Option<(ModuleRef<'a>, usize)> = None;
+        let mut depth = 0;
+        // where the attributes of the next module start
+        let mut attributes: Option<(usize, u32)> = None;
+        let mut offset = 0;
+        // the words of the line, before any comment
+        let mut words: Vec<&str> = vec![];
+        for (index, line) in input.split_inclusive('\n').enumerate() {
+            let indent = line.len() - line.trim_start_matches(characters::is_sep).len();
+            let at = offset + indent;
+            offset += line.len();
+            let number = index as u32 + 1;
+            words.clear();
+            words.extend(
+                line.split(|c| characters::is_sep(c) || c == '\r' || c == '\n')
+                    .filter(|word| !word.is_empty())
+                    .take_while(|word| !word.starts_with('#')),
+            );
+            let Some(&keyword) = words.first() else {
+                continue;
+            };
+            // a block is only opened or closed by a line its statement parser accepts
+            let statement = &line[indent..];
+            let parsed = |accepted: bool| accepted.then_some(()).ok_or_else(|| error(at));
+            let Some((module, start)) = open.as_mut() else {
+                match (keyword, words.len()) {
+                    ("autoidx", 2) => autoidx = Some(words[1].parse().map_err(|_| error(at))?),
+                    ("attribute", _) => {
+                        attributes.get_or_insert((at, number));
+                    }
+                    ("module", 2) => {
+                        parsed(accepts(statement, module::module_stmt))?;
+                        let (start, line) = attributes.take().unwrap_or((at, number));
+                        let module = ModuleRef {
+                            name: strip_id(words[1]).ok_or_else(|| error(at))?,
+                            source: "",
+                            line,
+                            wires: vec![],
+                            memories: vec![],
+                            cells: vec![],
+                            processes: vec![],
+                        };
+                        open = Some((module, start));
+                        depth = 1;
+                    }
+                    _ => return Err(error(at)),
+                }
+                continue;
+            };
+            let id = |word: &'a str| strip_id(word).ok_or_else(|| error(at));
+            match keyword {
+                "end" => {
+                    parsed(accepts(statement, module::module_end_stmt))?;
+                    depth -= 1;
+                    if depth == 0 {
+                        module.source = &input[*start..offset];
+                        modules.push(open.take().unwrap().0);
+                    }
+                }
+                "cell" => {
+                    parsed(accepts(statement, cell::cell_stmt))?;
+                    if depth == 1 {
+                        module.cells.push((id(words[2])?, id(words[1])?));
+                    }
+                    depth += 1;
+                }
+                "process" => {
+                    parsed(accepts(statement, process::process_stmt))?;
+                    if depth == 1 {
+                        module.processes.push(id(words[1])?);
+                    }
+                    depth += 1;
+                }
+                "switch" => {
+                    parsed(accepts(statement, switch::switch_stmt))?;
+                    depth += 1;
+                }
+                "module" => return Err(error(at)),
+                "wire" if depth == 1 => module.wires.push(id(words[words.len() - 1])?),
+                "memory" if depth == 1 => module.memories.push(id(words[words.len() - 1])?),
+                _ => {}
+            }
+        }
+        if open.is_some() || modules.is_empty() {
+            return Err(error(input.len()));
+        }
+        Ok(DesignRef { autoidx, modules })
+    }
+
+    /// The global autoindex value
+    pub fn autoidx(&self) -> Option<i32> {
+        self.autoidx
+    }
+
+    /// The modules, in the order of the input
+    pub fn modules(&self) -> &[ModuleRef<'a>] {
+        &self.modules
+    }
+
+    /// Get a module by name
+    pub fn module(&self, name: &str) -> Option<&ModuleRef<'a>> {
+        self.modules.iter().find(|module| module.name == name)
+    }
+
+    /// Parse every module, into the same design as `parse` would
+    pub fn to_design(&self) -> Result<Design, ParseError> {
+        let modules = self
+            .modules
+            .iter()
+            .map(|module| Ok((module.name.to_string(), module.to_module()?)))
+            .collect::<Result<HashMap<_, _>, ParseError>>()?;
+        let mut design = Design::from_iter(modules);
+        design.autoidx = self.autoidx;
+        Ok(design)
+    }
+}
+
+impl<'a> ModuleRef<'a> {
+    /// The name of the module
+    pub fn name(&self) -> &'a str {
+        self.name
+    }
+
+    /// The text of the module, from its first attribute to its `end`
+    pub fn source(&self) -> &'a str {
+        self.source
+    }
+
+    /// The names of the wires, in declaration order
+    pub fn wires(&self) -> &[&'a str] {
+        &self.wires
+    }
+
+    /// The names of the memories, in declaration order
+    pub fn memories(&self) -> &[&'a str] {
+        &self.memories
+    }
+
+    /// The names and types of the cells, in declaration order
+    pub fn cells(&self) -> &[(&'a str, &'a str)] {
+        &self.cells
+    }
+
+    /// The names of the processes, in declaration order
+    pub fn processes(&self) -> &[&'a str] {
+        &self.processes
+    }
+
+    /// Parse the module, errors locate lines within the whole input
+    pub fn to_module(&self) -> Result<Module, ParseError> {
+        crate::error::parse_complete(self.source, crate::module::module)
+            .map(|(_, module)| module)
+            .map_err(|mut e| {
+                e.shift_lines(self.line - 1);
+                e
+            })
+    }
+}
+
+/// Whether `parser` reads the statement on `line`
+fn accepts<'a, O>(line: &'a str, mut parser: impl FnMut(Span<'a>) -> IResult<Span<'a>, O>) -> bool {
+    parser(Span::new_extra(line, Default::default())).is_ok()
+}
+
+/// `word` without its sigil, if it is an id
+fn strip_id(word: &str) -> Option<&str> {
+    word.strip_prefix(['\\', '$'])
+        .filter(|name| !name.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use indoc::indoc;
+    use pretty_assertions::assert_eq;
+
+    const COUNTER: &str = include_str!("../tests/fixtures/counter.il");
+
+    #[test]
+    fn test_parse_borrowed() {
+        let owned = parse(COUNTER).unwrap();
+        let borrowed = parse_borrowed(COUNTER).unwrap();
+        assert_eq!(borrowed.autoidx(), *owned.autoidx());
+        assert_eq!(borrowed.modules().len(), 1);
+        let module = borrowed.module("counter").unwrap();
+        let counter = &owned.modules()["counter"];
+        let mut wires: Vec<&str> = counter
+            .wires()
+            .keys()
+            .map(|id| id.inner().as_str())
+            .collect();
+        wires.sort();
+        let mut borrowed_wires = module.wires().to_vec();
+        borrowed_wires.sort();
+        assert_eq!(borrowed_wires, wires);
+        for (name, cell_type) in module.cells() {
+            assert_eq!(counter.cells()[*name].cell_type(), cell_type);
+        }
+        assert_eq!(module.cells().len(), counter.cells().len());
+        for name in module.processes() {
+            assert!(counter.processes().contains_key(*name));
+        }
+        assert_eq!(module.processes().len(), counter.processes().len());
+        assert!(module
+            .source()
+            .starts_with("attribute \\src \"counter.v:1.1-12.10\"\n"));
+        assert_eq!(module.to_module().unwrap(), *counter);
+        assert_eq!(borrowed.to_design().unwrap(), owned);
+    }
+
+    #[test]
+    fn test_parse_borrowed_errors() {
+        // the body is only parsed on demand
+        let input = "module \\a\nend\n\nmodule \\b\n  wire foo \\c\nend\n";
+        let design = parse_borrowed(input).unwrap();
+        assert!(design.module("a").unwrap().to_module().is_ok());
+        let e = design.module("b").unwrap().to_module().unwrap_err();
+        assert_eq!(*e.line(), 5);
+        assert_eq!(e.snippet(), "  wire foo \\c");
+        let e = parse_borrowed("module \\a\n  cell $not $n\nend\n").unwrap_err();
+        assert_eq!(*e.line(), 4);
+        let e = parse_borrowed("module \\a\nend\nwire \\b\n").unwrap_err();
+        assert_eq!((*e.line(), e.snippet().as_str()), (3, "wire \\b"));
+        // a broken block start cannot be skipped, its `end` would close the module
+        let e = parse_borrowed("module \\a\n  cell $not\n  end\nend\n").unwrap_err();
+        assert_eq!((*e.line(), e.snippet().as_str()), (2, "  cell $not"));
+        let e = parse_borrowed("module \\a\n  process $p\n  end \\p\nend\n").unwrap_err();
+        assert_eq!(*e.line(), 3);
+    }
+
+    #[test]
+    fn test_parse_borrowed_blocks() {
+        let input = indoc! {r#"
+            module \a # module
+              wire \s
+              process $p
+                switch \s # switch
+                  case 1'1
+                    switch \s
+                    end
+                end # switch
+              end
+              cell $not $n
+              end # cell
+            end # module
+        "#};
+        let design = parse_borrowed(input).unwrap();
+        let module = design.module("a").unwrap();
+        assert_eq!(module.processes(), ["p"]);
+        assert_eq!(module.cells(), [("n", "not")]);
+        assert_eq!(module.source(), input);
+    }
+}
@@ -0,0 +1,269 @@
+//! Rewriting designs into a canonical form, so that designs meaning the same compare equal.
+
+use std::collections::HashMap;
+
+use crate::*;
+
+impl Design {
+    /// Rewrite every module in its canonical form, see `Module::canonicalize`
+    ///
+    /// Modules, wires, cells and the other named items are kept in maps, which compare equal
+    /// whatever their order, so only the contents of the modules need rewriting.
+    pub fn canonicalize(&mut self) {
+        self.modules.values_mut().for_each(Module::canonicalize);
+    }
+}
+
+impl Module {
+    /// Rewrite the module in a canonical form, without changing what it means
+    ///
+    /// * constant value bits are lowercase, a `Constant::Fill` is expanded to its bits, in
+    ///   signals, parameters and attributes alike
+    /// * a range of a single bit has no end, `\a [3:3]` becomes `\a [3]`
+    /// * a range over all the bits of a wire is the wire
+    /// * concatenations nested in concatenations are spliced in, a concatenation of a single
+    ///   signal is the signal
+    /// * module connections are sorted
+    ///
+    /// Process assignments and updates keep their order, the last one wins.
+    pub fn canonicalize(&mut self) {
+        let widths = self.net_width_map();
+        self.signals_mut(&mut |signal| canonicalize(signal, &widths));
+        self.constants_mut(&mut canonicalize_constant);
+        self.connections
+            .sort_by_cached_key(|connection| format!("{:?}", connection));
+    }
+
+    /// Call `f` on every constant of the module outside signals: attributes and parameters
+    fn constants_mut(&mut self, f: &mut impl FnMut(&mut Constant)) {
+        self.attributes.values_mut().for_each(&mut *f);
+        self.parameters.values_mut().flatten().for_each(&mut *f);
+        for wire in self.wires.values_mut() {
+            wire.attributes.values_mut().for_each(&mut *f);
+        }
+        for memory in self.memories.values_mut() {
+            memory.attributes.values_mut().for_each(&mut *f);
+        }
+        for cell in self.cells.values_mut() {
+            cell.attributes.values_mut().for_each(&mut *f);
+            cell.parameters.values_mut().for_each(&mut *f);
+        }
+        for process in self.processes.values_mut() {
+            process.attributes.values_mut().for_each(&mut *f);
+            let mut switches: Vec<&mut Switch> = process.switches.iter_mut().collect();
+            while let Some(switch) = switches.pop() {
+                switch.attributes.values_mut().for_each(&mut *f);
+                for case in &mut switch.cases {
+                    case.attributes.values_mut().for_each(&mut *f);
+                    for body in &mut case.case_bodies {
+                        if let CaseBody::Switch(switch) = body {
+                            switches.push(switch);
+                        }
+                    }
+                }
+            }
+            for sync in &mut process.syncs {
+                for update in &mut sync.updates {
+                    update.attributes.values_mut().for_each(&mut *f);
+                }
+                for memwr in sync.memwrs.values_mut() {
+                    memwr.attributes.values_mut().for_each(&mut *f);
+                }
+            }
+        }
+    }
+
+    /// Call `f` on every signal of the module: in connections, cells and processes
+    fn signals_mut(&mut self, f: &mut impl FnMut(&mut SigSpec)) {
+        for (dest, src) in &mut self.connections {
+            f(dest);
+            f(src);
+        }
+        for cell in self.cells.values_mut() {
+            cell.connections.values_mut().for_each(&mut *f);
+        }
+        for process in self.processes.values_mut() {
+            for (dest, src) in &mut process.assignments {
+                f(dest);
+                f(src);
+            }
+            let mut switches: Vec<&mut Switch> = process.switches.iter_mut().collect();
+            while let Some(switch) = switches.pop() {
+                f(&mut switch.switch_on_sigspec);
+                for case in &mut switch.cases {
+                    case.compare_against.iter_mut().flatten().for_each(&mut *f);
+                    for body in &mut case.case_bodies {
+                        match body {
+                            CaseBody::Assign((dest, src)) => {
+                                f(dest);
+                                f(src);
+                            }
+                            CaseBody::Switch(switch) => switches.push(switch),
+                        }
+                    }
+                }
+            }
+            for sync in &mut process.syncs {
+                if let SyncOn::Signal(_, signal) = &mut sync.sync_event {
+                    f(signal);
+                }
+                for update in &mut sync.updates {
+                    f(&mut update.dest);
+                    f(&mut update.src);
+                }
+                for memwr in sync.memwrs.values_mut() {
+                    f(&mut memwr.address);
+                    f(&mut memwr.data);
+                    f(&mut memwr.enable);
+                    f(&mut memwr.priority_mask);
+                }
+            }
+        }
+    }
+}
+
+/// Rewrite `constant` in its canonical form: lowercase bits, a fill expanded
+fn canonicalize_constant(constant: &mut Constant) {
+    if let Constant::Fill(bit, width) = constant {
+        *constant = Constant::Value(vec![*bit; *width]);
+    }
+    if let Constant::Value(bits) = constant {
+        bits.iter_mut().for_each(|bit| bit.make_ascii_lowercase());
+    }
+}
+
+/// Rewrite `signal` in its canonical form, `widths` are those of the wires of its module
+fn canonicalize(signal: &mut SigSpec, widths: &HashMap<String, usize>) {
+    match signal {
+        SigSpec::Constant(constant) => canonicalize_constant(constant),
+        SigSpec::WireId(_) => {}
+        SigSpec::Range(inner, start, end) => {
+            canonicalize(inner, widths);
+            if *end == Some(*start) {
+                *end = None;
+            }
+            if let SigSpec::WireId(name) = inner.as_ref() {
+                let whole = end.unwrap_or(*start) == 0 && Some(&(*start + 1)) == widths.get(name);
+                if whole {
+                    *signal = SigSpec::WireId(name.clone());
+                }
+            }
+        }
+        SigSpec::Concat(parts) => {
+            let mut spliced = Vec::with_capacity(parts.len());
+            for mut part in parts.drain(..) {
+                canonicalize(&mut part, widths);
+                match part {
+                    SigSpec::Concat(inner) => spliced.extend(inner),
+                    part => spliced.push(part),
+                }
+            }
+            *parts = spliced;
+            if parts.len() == 1 {
+                *signal = parts.pop().unwrap();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use indoc::indoc;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_canonicalize() {
+        let a = indoc! {r#"
+            module \m
+              wire width 4 \a
+              wire \b
+              cell $not $n
+                connect \A { \b }
+                connect \Y \a [0:0]
+              end
+              connect \a [3:0] { \b { \b 2'01 } }
+              connect \b \a [2:2]
+            end
+        "#};
+        let b = indoc! {r#"
+            module \m
+              wire \b
+              wire width 4 \a
+              connect \b \a [2]
+              connect \a { \b \b 2'01 }
+              cell $not $n
+                connect \Y \a [0]
+                connect \A \b
+              end
+            end
+        "#};
+        let mut a = parse(a).unwrap();
+        let mut b = parse(b).unwrap();
+        assert_ne!(a, b);
+        a.canonicalize();
+        b.canonicalize();
+        assert_eq!(a, b);
+        let module = &a.modules()["m"];
+        assert_eq!(module.cells()["n"].connections()["A"], SigSpec::wire("b"));
+        assert_eq!(module.connections()[0].0, SigSpec::wire("a"));
+    }
+
+    #[test]
+    fn test_canonicalize_constants() {
+        let a = indoc! {r#"
+            attribute \p 2'X1
+            module \m
+              parameter \W 4'1
+              attribute \init 2'ZZ
+              wire width 2 \q
+              attribute \keep 3'1
+              cell $not $n
+                parameter \P 4'X
+              end
+            end
+        "#};
+        let b = indoc! {r#"
+            attribute \p 2'x1
+            module \m
+              parameter \W 4'1111
+              attribute \init 2'zz
+              wire width 2 \q
+              attribute \keep 3'111
+              cell $not $n
+                parameter \P 4'xxxx
+              end
+            end
+        "#};
+        let exact = ParseOptions {
+            exact_values: true,
+            ..Default::default()
+        };
+        let mut a = parse_with(a, &exact).unwrap();
+        let mut b = parse(b).unwrap();
+        assert_ne!(a, b);
+        a.canonicalize();
+        b.canonicalize();
+        assert_eq!(a, b);
+        assert_eq!(
+            a.modules()["m"].cells()["n"].parameters()["P"],
+            Constant::Value(vec!['x'; 4])
+        );
+    }
+
+    #[test]
+    fn test_canonicalize_value_bits() {
+        let mut design = parse("module \\m\n  wire width 2 \\a\nend\n").unwrap();
+        let mut parts = design.module_mut("m").unwrap().clone().into_parts();
+        parts.connections.push((
+            SigSpec::wire("a"),
+            SigSpec::Constant(Constant::Value(vec!['X', 'Z'])),
+        ));
+        *design.module_mut("m").unwrap() = Module::from(parts);
+        design.canonicalize();
+        assert_eq!(
+            design.modules()["m"].connections()[0].1,
+            SigSpec::value("zx")
+        );
+    }
+}
@@ -10,6 +10,9 @@
 //!                      |  connect <id> <sigspec> <eol>
 //! <cell-end-stmt>     ::= end <eol>
 //! ```
+//!
+//! In lenient mode, `connect <sigspec> <eol>` connects the next unnamed port, see
+//! `ParseOptions::lenient`.
 
 use std::collections::HashMap;
 
@@ -17,29 +20,105 @@ use crate::*;
 use nom::{
     branch::alt,
     bytes::complete::tag,
-    combinator::{map, opt},
+    combinator::{map, opt, value, verify},
     multi::many0,
     sequence::terminated,
-    IResult,
 };
 use nom_tracable::tracable_parser;
 
+impl Cell {
+    /// A cell of type `cell_type`, ie. `$and` or the name of a module, without any parameter
+    /// or connection
+    ///
+    /// Names are stored without their sigil, which is stripped if given.
+    ///
+    /// ```
+    /// use rtlicious::{Cell, Constant, SigSpec};
+    ///
+    /// let and = Cell::new("$and")
+    ///     .with_param("A_WIDTH", Constant::Integer(1))
+    ///     .with_param("B_WIDTH", Constant::Integer(1))
+    ///     .with_param("Y_WIDTH", Constant::Integer(1))
+    ///     .with_connection("A", SigSpec::wire("a"))
+    ///     .with_connection("B", SigSpec::wire("b"))
+    ///     .with_connection("\\Y", SigSpec::wire("y"));
+    /// assert_eq!(and.cell_type(), "and");
+    /// assert_eq!(and.parameters()["A_WIDTH"], Constant::Integer(1));
+    /// assert_eq!(and.connections()["Y"], SigSpec::wire("y"));
+    /// ```
+    pub fn new(cell_type: impl Into<String>) -> Cell {
+        let cell_type = cell_type.into();
+        Cell {
+            attributes: HashMap::new(),
+            cell_type: strip_sigil(&cell_type).to_string(),
+            parameters: HashMap::new(),
+            parameter_kinds: HashMap::new(),
+            connections: HashMap::new(),
+        }
+    }
+
+    /// Set the parameter `name` to `value`
+    pub fn with_param(mut self, name: &str, value: Constant) -> Cell {
+        self.parameters.insert(strip_sigil(name).to_string(), value);
+        self
+    }
+
+    /// Connect the port `name` to `signal`
+    pub fn with_connection(mut self, name: &str, signal: SigSpec) -> Cell {
+        self.connections
+            .insert(strip_sigil(name).to_string(), signal);
+        self
+    }
+}
+
+impl Cell {
+    /// The direction of the port `port` if the cell is a Yosys internal cell with such a port,
+    /// see `cell_port_directions`
+    pub fn port_direction(&self, port: &str) -> Option<PortDir> {
+        let port = strip_sigil(port);
+        cell_port_directions(&self.cell_type)?
+            .iter()
+            .find(|(name, _)| *name == port)
+            .map(|(_, direction)| *direction)
+    }
+}
+
+fn strip_sigil(name: &str) -> &str {
+    name.strip_prefix(['$', '\\']).unwrap_or(name)
+}
+
 #[tracable_parser]
 pub(crate) fn cell(input: Span) -> IResult<Span, (String, Cell)> {
-    let (input, _) = many0(attribute::attr_stmt)(input)?;
+    let (input, attributes) = many0(attribute::attr_stmt)(input)?;
     let (input, info) = cell_stmt(input)?;
 
     let mut parameters: HashMap<String, Constant> = HashMap::new();
+    let mut parameter_kinds: HashMap<String, ParameterKind> = HashMap::new();
     let mut connections: HashMap<String, SigSpec> = HashMap::new();
+    // the number of connections without a port name
+    let mut positional = 0;
 
     let (input, _) = many0(|input| {
         alt((
-            map(cell_body_stmt_param, |(id, constant)| {
+            map(cell_body_stmt_param, |(id, kind, constant)| {
+                if let Some(kind) = kind {
+                    parameter_kinds.insert(id.clone(), kind);
+                }
                 parameters.insert(id, constant);
             }),
-            map(cell_connect_stmt, |(id1, id2)| {
-                connections.insert(id1, id2);
-            }),
+            map(
+                alt((
+                    map(cell_connect_stmt, |(port, signal)| (Some(port), signal)),
+                    map(cell_positional_connect_stmt, |signal| (None, signal)),
+                )),
+                |(port, signal)| {
+                    let port = port.unwrap_or_else(|| {
+                        positional += 1;
+                        format!("${}", positional - 1)
+                    });
+                    connections.insert(port, signal);
+                },
+            ),
         ))(input)
     })(input)?;
 
@@ -50,8 +129,10 @@ pub(crate) fn cell(input: Span) -> IResult<Span, (String, Cell)> {
         (
             info.1,
             Cell {
+                attributes: attributes.into_iter().collect(),
                 cell_type: info.0,
                 parameters,
+                parameter_kinds,
                 connections,
             },
         ),
@@ -83,22 +164,23 @@ pub(crate) fn cell_type(input: Span) -> IResult<Span, String> {
 
 ///  <cell-body-stmt>    ::= parameter (signed | real)? <id> <constant> <eol>
 ///                      |  connect <id> <sigspec> <eol>
-pub(crate) fn cell_body_stmt_param(input: Span) -> IResult<Span, (String, Constant)> {
+pub(crate) fn cell_body_stmt_param(
+    input: Span,
+) -> IResult<Span, (String, Option<ParameterKind>, Constant)> {
     let (input, _) = tag("parameter")(input)?;
     let (input, _) = characters::sep(input)?;
-    let (input, signed) = opt(terminated(tag("signed"), characters::sep))(input)?;
-    let (input, real) = opt(terminated(tag("real"), characters::sep))(input)?;
-    if signed.is_some() || real.is_some() {
-        log::warn!(
-            "signed or real not implemented, found at at line {}",
-            input.location_line(),
-        );
-    }
+    let (input, kind) = opt(terminated(
+        alt((
+            value(ParameterKind::Signed, tag("signed")),
+            value(ParameterKind::Real, tag("real")),
+        )),
+        characters::sep,
+    ))(input)?;
     let (input, id) = identifier::id(input)?;
     let (input, _) = characters::sep(input)?;
     let (input, constant) = constant::constant(input)?;
     let (input, _) = characters::eol(input)?;
-    Ok((input, (id.erease(), constant)))
+    Ok((input, (id.erease(), kind, constant)))
 }
 
 ///  connect <id> <sigspec> <eol>
@@ -113,6 +195,20 @@ pub(crate) fn cell_connect_stmt(input: Span) -> IResult<Span, (String, SigSpec)>
     Ok((input, (id1.erease(), id2)))
 }
 
+///  connect <sigspec> <eol>
+///
+/// Only accepted in lenient mode, the port is named after the position of the connection
+/// among the unnamed ones: `$0`, `$1`...
+fn cell_positional_connect_stmt(input: Span) -> IResult<Span, SigSpec> {
+    let start = input;
+    let (input, _) = verify(tag("connect"), |_| ParseOptions::lenient())(input)?;
+    let (input, _) = characters::sep(input)?;
+    let (input, signal) = crate::sigspec::sigspec(input)?;
+    let (input, _) = characters::eol(input)?;
+    ParseOptions::report(start, "cell connection without a port name");
+    Ok((input, signal))
+}
+
 /// <cell-end-stmt>     ::= end <eol>
 pub(crate) fn cell_end_stmt(input: Span) -> IResult<Span, &str> {
     let (input, _) = tag("end")(input)?;
@@ -157,8 +253,87 @@ mod tests {
         let info: TracableInfo = TracableInfo::new().parser_width(64).fold("term");
         for (i, (input, expected)) in vectors.iter().enumerate() {
             let span = Span::new_extra(*input, info);
+            let (_, (name, _, constant)) = cell_body_stmt_param(span).unwrap();
+            assert_eq!((name, constant), *expected, "Test case {}", i);
+        }
+    }
+
+    #[test]
+    fn test_cell_body_stmt_kinds() {
+        let vectors = [
+            (
+                "parameter \\WIDTH 6\n",
+                ("WIDTH".to_string(), None, Constant::Integer(6)),
+            ),
+            (
+                "parameter signed \\SOME_SIGNED 0\n",
+                (
+                    "SOME_SIGNED".to_string(),
+                    Some(ParameterKind::Signed),
+                    Constant::Integer(0),
+                ),
+            ),
+            (
+                "parameter real \\SOME_REAL \"1.5\"\n",
+                (
+                    "SOME_REAL".to_string(),
+                    Some(ParameterKind::Real),
+                    Constant::String("1.5".to_string()),
+                ),
+            ),
+        ];
+        for (input, expected) in vectors {
+            let span = Span::new_extra(input, Default::default());
             let ret = cell_body_stmt_param(span).unwrap();
-            assert_eq!(ret.1, *expected, "Test case {}", i);
+            assert_eq!(ret.1, expected, "failed: {}", input);
+        }
+    }
+
+    #[test]
+    fn test_cell_param_string_with_spaces() {
+        let vectors = [
+            (
+                "parameter \\TRANS_TABLE \"0 1 2\"\n",
+                (
+                    "TRANS_TABLE".to_string(),
+                    None,
+                    Constant::String("0 1 2".to_string()),
+                ),
+            ),
+            (
+                "parameter \\NAME \"2 'a\"\n",
+                (
+                    "NAME".to_string(),
+                    None,
+                    Constant::String("2 'a".to_string()),
+                ),
+            ),
+            (
+                "parameter \\CTRL_IN_WIDTH 12\n",
+                ("CTRL_IN_WIDTH".to_string(), None, Constant::Integer(12)),
+            ),
+            (
+                "parameter \\TRANS_TABLE 12'0000x1xx1010\n",
+                (
+                    "TRANS_TABLE".to_string(),
+                    None,
+                    Constant::Value("0101xx1x0000".chars().collect()),
+                ),
+            ),
+        ];
+        for (input, expected) in vectors {
+            let span = Span::new_extra(input, Default::default());
+            let ret = cell_body_stmt_param(span).unwrap();
+            assert_eq!(ret.1, expected, "failed: {}", input);
+        }
+        // strings that start like an integer or a value
+        for string in ["0 1 2", "2'10"] {
+            let input = format!("\"{}\"", string);
+            let span = Span::new_extra(&input, Default::default());
+            assert_eq!(
+                constant::constant(span).unwrap().1,
+                Constant::String(string.to_string())
+            );
         }
     }
 
@@ -213,6 +388,83 @@ mod tests {
             assert_eq!(ret.1, *expected, "Test case {}", i);
         }
     }
+    #[test]
+    fn test_cell_positional_connect() {
+        let input = indoc! {r#"
+            cell \sub $s
+              connect \a
+              connect \CLK \clk
+              connect { \b 1'0 }
+            end
+        "#};
+        assert!(cell(Span::new_extra(input, Default::default())).is_err());
+        let lenient = ParseOptions {
+            lenient: true,
+            ..Default::default()
+        };
+        let (_, (_, cell)) = lenient
+            .scope(|| cell(Span::new_extra(input, Default::default())))
+            .unwrap();
+        assert_eq!(
+            cell.connections,
+            HashMap::from([
+                ("$0".to_string(), SigSpec::wire("a")),
+                ("CLK".to_string(), SigSpec::wire("clk")),
+                (
+                    "$1".to_string(),
+                    SigSpec::concat([SigSpec::wire("b"), SigSpec::value("0")])
+                ),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_port_direction() {
+        let dff = Cell::new("$dff");
+        assert_eq!(dff.port_direction("CLK"), Some(PortDir::Input));
+        assert_eq!(dff.port_direction("D"), Some(PortDir::Input));
+        assert_eq!(dff.port_direction("\\Q"), Some(PortDir::Output));
+        assert_eq!(dff.port_direction("Y"), None);
+
+        let add = Cell::new("$add");
+        for port in ["A", "B"] {
+            assert_eq!(add.port_direction(port), Some(PortDir::Input));
+        }
+        assert_eq!(add.port_direction("Y"), Some(PortDir::Output));
+        assert_eq!(cell_port_directions("add"), cell_port_directions("$add"));
+
+        assert_eq!(Cell::new("my_module").port_direction("Y"), None);
+        assert_eq!(cell_port_directions("$alu").unwrap().len(), 7);
+    }
+
+    #[test]
+    fn test_specify_cells() {
+        let design = Design::new_from_str(include_str!("../tests/fixtures/specify.il")).unwrap();
+        let module = &design.modules()["dff_timing"];
+        assert_eq!(module.cells().len(), 4);
+        assert_eq!(module.validate(), Ok(()));
+
+        let specify3 = &module.cells()["specify$2"];
+        assert_eq!(specify3.cell_type(), "specify3");
+        assert_eq!(specify3.parameters().len(), 15);
+        assert_eq!(specify3.parameters()["T_RISE_MAX"], Constant::Integer(250));
+        // a 64 bit delay parses like the 32 bit integers next to it
+        let t_fall_max = &specify3.parameters()["T_FALL_MAX"];
+        assert_eq!(t_fall_max, &Constant::from_i64_width(250, 64));
+        assert_eq!(t_fall_max.as_i64(), Some(250));
+        assert_eq!(specify3.connections()["EN"], SigSpec::value("1"));
+
+        let specrule = &module.cells()["specify$3"];
+        assert_eq!(
+            specrule.parameters()["TYPE"],
+            Constant::String("$setup".to_string())
+        );
+        assert_eq!(
+            Design::new_from_str(&design.to_rtlil_string()).unwrap(),
+            design
+        );
+    }
+
     #[test]
     fn test_cell() {
         let vectors = vec![(
@@ -229,6 +481,7 @@ mod tests {
             (
                 "flatten\\alu.$add$serv_alu.v:39$15".to_string(),
                 Cell {
+                    attributes: HashMap::new(),
                     cell_type: "add".to_string(),
                     parameters: vec![
                         ("A_SIGNED".to_string(), Constant::Integer(0)),
@@ -239,6 +492,7 @@ mod tests {
                     ]
                     .into_iter()
                     .collect(),
+                    parameter_kinds: HashMap::new(),
                     connections: vec![("A".to_string(), SigSpec::WireId("alu.i_rs1".to_string()))]
                         .into_iter()
                         .collect(),
@@ -250,4 +504,47 @@ mod tests {
             assert_eq!(cell(span).unwrap().1, expected);
         }
     }
+
+    #[test]
+    fn test_cell_new() {
+        let input = indoc! {r#"
+            cell $and $x
+              parameter \A_WIDTH 1
+              connect \A \a
+              connect \Y $y
+            end
+        "#};
+        let span = Span::new_extra(input, Default::default());
+        let built = Cell::new("$and")
+            .with_param("\\A_WIDTH", Constant::Integer(1))
+            .with_connection("A", SigSpec::wire("a"))
+            .with_connection("Y", SigSpec::wire("y"));
+        assert_eq!(cell(span).unwrap().1 .1, built);
+        assert_eq!(Cell::new("\\sub").cell_type(), "sub");
+    }
+
+    #[test]
+    fn test_cell_parameter_kinds_do_not_warn() {
+        let diagnostics = std::sync::Arc::new(std::sync::Mutex::new(vec![]));
+        let sink = diagnostics.clone();
+        let options = ParseOptions {
+            lenient: true,
+            diagnostics: Some(DiagnosticSink::new(move |diagnostic| {
+                sink.lock().unwrap().push(diagnostic)
+            })),
+            ..Default::default()
+        };
+        let mut input = String::from("module \\m\ncell $mul $m\n");
+        for i in 0..100 {
+            input.push_str(&format!("  parameter signed \\S{} {}\n", i, i));
+            input.push_str(&format!("  parameter real \\R{} \"{}.5\"\n", i, i));
+        }
+        input.push_str("end\nend\n");
+        let design = parse_with(&input, &options).unwrap();
+        assert_eq!(
+            design.modules()["m"].cells()["m"].parameter_kinds().len(),
+            200
+        );
+        assert!(diagnostics.lock().unwrap().is_empty());
+    }
 }
@@ -3,31 +3,51 @@
 //! characters as consecutive single-byte characters. While other encodings
 //! may work, UTF-8 is known to be safe to use. Byte order marks at the
 //! beginning of the file will cause an error.
-//! ASCII spaces (32) and tabs (9) separate lexer tokens.
+//! ASCII spaces (32) and tabs (9) separate lexer tokens. A stray carriage return (13) between
+//! them, left by mixed line endings, is part of the separator. In lenient mode, form feeds (12)
+//! and vertical tabs (11) separate tokens too, they never end a line.
 //!
 //! A `nonws` character, used in identifiers, is any character whose
 //! encoding consists solely of bytes above ASCII space (32).
 //!
 //! An eol is one or more consecutive ASCII newlines (10) and carriage
-//! returns (13).
+//! returns (13). A comment may precede the eol on the same line, as in
+//! `end # module foo`.
 
-use crate::{string, Span};
+use crate::{string, IResult, ParseOptions, Span};
 use nom::{
     branch::alt,
     bytes::complete::{tag, take_while, take_while1},
+    character::complete::satisfy,
+    combinator::{eof, map, opt, peek, verify},
     multi::{many0, many1},
-    IResult,
+    sequence::{pair, preceded, terminated},
 };
 
 pub(crate) fn is_sep(chr: char) -> bool {
     chr == ' ' || chr == '\t'
 }
 /// ASCII spaces (32) and tabs (9) separate lexer tokens.
+/// Carriage returns are accepted in between as long as a space or tab follows them, any other
+/// carriage return ends the line.
 pub(crate) fn sep(input: Span) -> IResult<Span, ()> {
-    let (input, _) = take_while1(is_sep)(input)?;
+    let (input, _) = many1(alt((
+        take_while1(is_sep),
+        terminated(take_while1(|c| c == '\r'), peek(satisfy(is_sep))),
+        lenient_sep,
+    )))(input)?;
     Ok((input, ()))
 }
 
+/// Form feeds (12) and vertical tabs (11), separators in lenient mode only
+fn lenient_sep(input: Span) -> IResult<Span, Span> {
+    let (rest, run) = verify(take_while1(|c| c == '\x0c' || c == '\x0b'), |_| {
+        ParseOptions::lenient()
+    })(input)?;
+    ParseOptions::report(input, "form feed or vertical tab read as a space");
+    Ok((rest, run))
+}
+
 // A nonws character, used in identifiers, is any character whose encoding consists solely of bytes above ASCII space (32).
 // this is inlined in id because a Vec<char> is returned which is not very usefull because we would have to build the str back.
 #[allow(dead_code)]
@@ -36,17 +56,35 @@ pub fn nonws(input: Span) -> IResult<Span, char> {
     Ok((input, nonws))
 }
 
+/// A comment trailing a statement on the same line, ie. ` # module foo`.
+/// The line ending itself is left for `eol`.
+fn inline_comment(input: Span) -> IResult<Span, ()> {
+    let (input, _) = pair(
+        take_while(is_sep),
+        preceded(tag("#"), take_while(|c| c != '\n' && c != '\r')),
+    )(input)?;
+    Ok((input, ()))
+}
+
 /// consume eol
 /// An eol is one or more consecutive ASCII newlines (10) and carriage returns (13).
+/// An inline comment before the newline is tolerated.
 pub fn eol(input: Span) -> IResult<Span, ()> {
+    let (input, _) = opt(inline_comment)(input)?;
     let (input, _) = many1(alt((tag("\n"), tag("\r"))))(input)?;
     // eat comments if any
     let (input, _) = many0(string::comment)(input)?;
     // eat whitespace if any
-    let (input, _) = take_while(is_sep)(input)?;
+    let (input, _) = many0(alt((take_while1(is_sep), lenient_sep)))(input)?;
     Ok((input, ()))
 }
 
+/// consume eol, or accept the end of the input in its place.
+/// Used for the last statement of a file, which may lack a trailing newline.
+pub fn eol_or_eof(input: Span) -> IResult<Span, ()> {
+    alt((eol, map(pair(opt(inline_comment), eof), |_| ())))(input)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -75,6 +113,97 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_carriage_returns() {
+        // a carriage return followed by a space or a tab is a separator
+        for (input, expected) in [
+            (" \r \\a", "\\a"),
+            ("\r\t\\a", "\\a"),
+            (" \r\r  1", "1"),
+            // a carriage return ending the line is not a separator
+            (" \r\n", "\r\n"),
+            (" \r\\a", "\r\\a"),
+        ] {
+            let span = Span::new_extra(input, Default::default());
+            assert_eq!(
+                sep(span).unwrap().0.fragment(),
+                &expected,
+                "failed: {:?}",
+                input
+            );
+        }
+        for input in ["", "\r", "\r\n", "\r\\a"] {
+            assert!(sep(Span::new_extra(input, Default::default())).is_err());
+        }
+
+        // line endings of every kind, and carriage returns inside statements, in a whole module
+        let input = indoc::indoc! {r#"
+        autoidx 2
+        attribute \top 1
+        module \crlf
+          wire width 2 input 1 \a
+          wire width 2 output 2 \b
+          cell $not $not$1
+            parameter \Y_WIDTH 2
+            connect \A \a
+            connect \Y \b
+          end
+          process $proc$1
+            switch \a [0]
+              case 1'1 # comment
+                assign \b \a
+              case 1'0
+            end
+            sync always
+              update \b \a
+          end
+        end
+        "#};
+        let expected = crate::Design::new_from_str(input).unwrap();
+        let crlf = input.replace('\n', "\r\n");
+        assert_eq!(crate::Design::new_from_str(&crlf).unwrap(), expected);
+        let cr = input.replace('\n', "\r");
+        assert_eq!(crate::Design::new_from_str(&cr).unwrap(), expected);
+        // carriage returns left in the middle of statements
+        let stray = crlf
+            .replace("width 2 input", "width 2\r input")
+            .replace("connect \\A \\a", "connect \\A\r \\a");
+        assert_eq!(crate::Design::new_from_str(&stray).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_sep_form_feed() {
+        let span = |input| Span::new_extra(input, Default::default());
+        // only spaces and tabs in strict mode
+        for input in ["\x0c\\a", "\x0b\\a"] {
+            assert!(!is_sep(input.chars().next().unwrap()));
+            assert!(sep(span(input)).is_err());
+        }
+        assert_eq!(eol(span("\n\x0c\\a")).unwrap().0.fragment(), &"\x0c\\a");
+        let lenient = ParseOptions {
+            lenient: true,
+            ..Default::default()
+        };
+        lenient.scope(|| {
+            for input in ["\x0c\\a", " \x0b\t\\a", "\x0c \x0c\\a"] {
+                assert_eq!(
+                    sep(span(input)).unwrap().0.fragment(),
+                    &"\\a",
+                    "{:?}",
+                    input
+                );
+            }
+            assert_eq!(eol(span("\n\x0c  \\a")).unwrap().0.fragment(), &"\\a");
+            // they are no line ending
+            assert!(eol(span("\x0c")).is_err());
+            assert!(eol(span("\x0b\\a")).is_err());
+        });
+        let input = "module \\a\n  wire\x0cwidth 2\x0b\\b\nend\n";
+        assert!(crate::parse(input).is_err());
+        let design = crate::parse_with(input, &lenient).unwrap();
+        assert_eq!(*design.modules()["a"].wire("b").unwrap().width(), 2);
+    }
+
     #[test]
     fn test_nonws() {
         let vectors = [
@@ -121,4 +250,42 @@ mod tests {
             assert_eq!(ret.unwrap().0.fragment(), expected, "Test case {}", i);
         }
     }
+
+    #[test]
+    fn test_inline_comment() {
+        // a comment may end the line of a statement
+        for (input, expected) in [
+            (" # comment\n", ""),
+            ("\t# comment\r\n", ""),
+            ("# comment\n", ""),
+            (" # comment\n  wire", "wire"),
+        ] {
+            let span = Span::new_extra(input, Default::default());
+            assert_eq!(
+                eol(span).unwrap().0.fragment(),
+                &expected,
+                "failed: {:?}",
+                input
+            );
+        }
+        for input in ["end # comment\n", "end # module \\comb_not1\n"] {
+            let span = Span::new_extra(input, Default::default());
+            assert!(crate::module::module_end_stmt(span).unwrap().0.is_empty());
+            assert!(crate::cell::cell_end_stmt(span).unwrap().0.is_empty());
+        }
+    }
+
+    #[test]
+    fn test_eol_or_eof() {
+        let vectors = [("\n", ""), ("", ""), (" # comment", ""), ("\r\n", "")];
+        let info = TracableInfo::new().parser_width(64).fold("term");
+        for (i, (input, expected)) in vectors.iter().enumerate() {
+            let span = LocatedSpan::new_extra(*input, info);
+            let ret = eol_or_eof(span);
+            assert!(ret.is_ok(), "Test case {}", i);
+            assert_eq!(ret.unwrap().0.fragment(), expected, "Test case {}", i);
+        }
+        let span = LocatedSpan::new_extra("x", info);
+        assert!(eol_or_eof(span).is_err());
+    }
 }
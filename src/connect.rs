@@ -1,5 +1,5 @@
 use crate::*;
-use nom::{bytes::complete::tag, IResult};
+use nom::bytes::complete::tag;
 use nom_tracable::tracable_parser;
 
 ///  <conn-stmt> ::= connect <sigspec> <sigspec> <eol>
@@ -7,9 +7,7 @@ use nom_tracable::tracable_parser;
 pub(crate) fn conn_stmt(input: Span) -> IResult<Span, (SigSpec, SigSpec)> {
     let (input, _) = tag("connect")(input)?;
     let (input, _) = characters::sep(input)?;
-    let (input, sig1) = sigspec::sigspec(input)?;
-    let (input, _) = characters::sep(input)?;
-    let (input, sig2) = sigspec::sigspec(input)?;
+    let (input, (sig1, sig2)) = sigspec::sigspec_pair(input)?;
     let (input, _) = characters::eol(input)?;
     Ok((input, (sig1, sig2)))
 }
@@ -38,4 +36,24 @@ mod tests {
             )
         );
     }
+
+    #[test]
+    fn test_conn_stmt_concat_dest() {
+        let expected = (
+            SigSpec::concat([SigSpec::wire("a"), SigSpec::wire("b")]),
+            SigSpec::wire("c"),
+        );
+        for input in ["connect { \\a \\b } \\c\n", "connect { \\a \\b }\\c\n"] {
+            let span = Span::new_extra(input, Default::default());
+            assert_eq!(conn_stmt(span).unwrap().1, expected, "failed: {}", input);
+        }
+        let span = Span::new_extra("connect \\a [1:0]1'0\n", Default::default());
+        assert_eq!(
+            conn_stmt(span).unwrap().1,
+            (SigSpec::range("a", 1, Some(0)), SigSpec::value("0"))
+        );
+        // a wire id runs until the next whitespace
+        let span = Span::new_extra("connect \\a\\b\n", Default::default());
+        assert!(conn_stmt(span).is_err());
+    }
 }
@@ -1,19 +1,205 @@
 //! Constant parser
 //! ```text
-//! <constant>          ::= <value> | <integer> | <string>
+//! <constant>          ::= <value> | <real> | <integer> | <string>
 //! ```
 
-use crate::{string, value, Constant, Span};
-use nom::{branch::alt, combinator::map, IResult};
+use std::borrow::Cow;
+
+use crate::{error, string, value, Constant, ConstantKind, IResult, ParseError, Span};
+use nom::{branch::alt, combinator::map};
 use nom_tracable::tracable_parser;
 
-/// <constant>          ::= <value> | <integer> | <string>
+impl Constant {
+    /// The variant of the constant, without its contents
+    ///
+    /// `Constant` may gain variants, so matches over it outside this crate need a wildcard arm.
+    ///
+    /// ```
+    /// use rtlicious::{Constant, ConstantKind};
+    ///
+    /// let constant = Constant::Integer(1);
+    /// assert_eq!(constant.kind(), ConstantKind::Integer);
+    /// let width = match constant {
+    ///     Constant::Value(bits) => Some(bits.len()),
+    ///     Constant::Integer(_) => Some(32),
+    ///     Constant::String(s) => Some(8 * s.len()),
+    ///     _ => None,
+    /// };
+    /// assert_eq!(width, Some(32));
+    /// ```
+    ///
+    /// Without the wildcard arm, the match does not compile:
+    ///
+    /// ```compile_fail
+    /// use rtlicious::Constant;
+    ///
+    /// match Constant::Integer(1) {
+    ///     Constant::Value(_) | Constant::Integer(_) | Constant::String(_) | Constant::Real(_) => {}
+    ///     Constant::Fill(..) => {}
+    /// }
+    /// ```
+    pub fn kind(&self) -> ConstantKind {
+        match self {
+            Constant::Value(_) => ConstantKind::Value,
+            Constant::Integer(_) => ConstantKind::Integer,
+            Constant::String(_) => ConstantKind::String,
+            Constant::Real(_) => ConstantKind::Real,
+            Constant::Fill(_, _) => ConstantKind::Fill,
+        }
+    }
+
+    /// The constant with a `Fill` turned into the `Value` it stands for, other constants as is
+    ///
+    /// ```
+    /// use rtlicious::Constant;
+    ///
+    /// let fill = Constant::Fill('1', 3);
+    /// assert_eq!(*fill.expanded(), Constant::Value(vec!['1', '1', '1']));
+    /// ```
+    pub fn expanded(&self) -> Cow<'_, Constant> {
+        match self {
+            Constant::Fill(bit, width) => Cow::Owned(Constant::Value(vec![*bit; *width])),
+            _ => Cow::Borrowed(self),
+        }
+    }
+
+    /// Build a `width` bits wide value holding the 2's complement form of `value`
+    ///
+    /// A `value` that does not fit in `width` bits is truncated to its `width` least significant
+    /// bits, as a Verilog assignment would. Wider than 64 bits, `value` is sign extended.
+    ///
+    /// ```
+    /// use rtlicious::Constant;
+    ///
+    /// assert_eq!(
+    ///     Constant::from_i64_width(10, 4),
+    ///     Constant::Value(vec!['0', '1', '0', '1'])
+    /// );
+    /// assert_eq!(
+    ///     Constant::from_i64_width(-1, 3),
+    ///     Constant::Value(vec!['1', '1', '1'])
+    /// );
+    /// ```
+    pub fn from_i64_width(value: i64, width: usize) -> Constant {
+        let bits = (0..width)
+            .map(|i| {
+                // shifting an i64 is arithmetic, the sign bit is repeated past bit 63
+                if (value >> i.min(63)) & 1 == 1 {
+                    '1'
+                } else {
+                    '0'
+                }
+            })
+            .collect();
+        Constant::Value(bits)
+    }
+
+    /// The bit at `index`, 0 being the least significant bit
+    ///
+    /// Integers are 32 bits wide, in 2's complement form. Strings and reals have no bits.
+    ///
+    /// ```
+    /// use rtlicious::Constant;
+    ///
+    /// let value = Constant::try_from("4'1000").unwrap();
+    /// assert_eq!(value.bit_at(3), Some('1'));
+    /// assert_eq!(value.bit_at(4), None);
+    /// assert_eq!(Constant::Integer(-1).bit_at(31), Some('1'));
+    /// ```
+    pub fn bit_at(&self, index: usize) -> Option<char> {
+        match self {
+            // the bits are stored least significant first
+            Constant::Value(bits) => bits.get(index).copied(),
+            Constant::Fill(bit, width) if index < *width => Some(*bit),
+            Constant::Integer(i) if index < 32 => {
+                Some(if (i >> index) & 1 == 1 { '1' } else { '0' })
+            }
+            Constant::Integer(_)
+            | Constant::Fill(_, _)
+            | Constant::String(_)
+            | Constant::Real(_) => None,
+        }
+    }
+
+    /// The integer held by the constant, if it has one
+    ///
+    /// Values are read as unsigned and must only hold `0` and `1` bits that fit in an `i64`.
+    /// Strings have no integer value.
+    ///
+    /// ```
+    /// use rtlicious::Constant;
+    ///
+    /// assert_eq!(Constant::from_i64_width(10, 4).as_i64(), Some(10));
+    /// assert_eq!(Constant::Integer(-3).as_i64(), Some(-3));
+    /// assert_eq!(Constant::Value(vec!['x']).as_i64(), None);
+    /// ```
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            Constant::Integer(i) => Some(i64::from(*i)),
+            Constant::Value(bits) => {
+                bits.iter()
+                    .enumerate()
+                    .rev()
+                    .try_fold(0i64, |acc, (i, bit)| match bit {
+                        '0' => Some(acc),
+                        '1' if i < 63 => Some(acc | 1 << i),
+                        _ => None,
+                    })
+            }
+            Constant::Fill(_, _) => self.expanded().as_i64(),
+            Constant::String(_) | Constant::Real(_) => None,
+        }
+    }
+
+    /// The value of a real constant
+    ///
+    /// ```
+    /// use rtlicious::Constant;
+    ///
+    /// assert_eq!(Constant::try_from("-2.5e1").unwrap().as_f64(), Some(-25.0));
+    /// assert_eq!(Constant::Integer(3).as_f64(), None);
+    /// ```
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Constant::Real(real) => real.parse().ok(),
+            _ => None,
+        }
+    }
+}
+
+impl TryFrom<&str> for Constant {
+    type Error = ParseError;
+
+    /// Parse a constant from its textual form
+    ///
+    /// ```
+    /// use rtlicious::Constant;
+    ///
+    /// assert_eq!(Constant::try_from("-3").unwrap(), Constant::Integer(-3));
+    /// assert_eq!(
+    ///     Constant::try_from("2'10").unwrap(),
+    ///     Constant::Value(vec!['0', '1'])
+    /// );
+    /// assert_eq!(
+    ///     Constant::try_from("\"top.v:1.2-3.4\"").unwrap(),
+    ///     Constant::String("top.v:1.2-3.4".to_string())
+    /// );
+    /// assert!(Constant::try_from("2'10 ").is_err());
+    /// ```
+    fn try_from(input: &str) -> Result<Self, Self::Error> {
+        error::parse_complete(input, constant)
+    }
+}
+
+/// <constant>          ::= <value> | <real> | <integer> | <string>
 #[tracable_parser]
 pub(crate) fn constant(input: Span) -> IResult<Span, Constant> {
     // map the result of the alt combinator to the Constant enum
     let (input, constant) = alt((
-        // if the input is a value, return a Constant::Value
-        map(value::value, Constant::Value),
+        // if the input is a value, return a Constant::Value, or a Constant::Fill
+        value::value,
+        // a real starts like an integer, it is tried first
+        map(value::real, Constant::Real),
         // if the input is an integer, return a Constant::Integer
         map(value::integer, Constant::Integer),
         // if the input is a string, return a Constant::String
@@ -25,6 +211,24 @@ pub(crate) fn constant(input: Span) -> IResult<Span, Constant> {
 #[cfg(test)]
 mod tests {
     use super::*;
+
+    #[test]
+    fn test_bit_at() {
+        let value = Constant::try_from("4'1010").unwrap();
+        let bits: Vec<Option<char>> = (0..5).map(|i| value.bit_at(i)).collect();
+        assert_eq!(bits, [Some('0'), Some('1'), Some('0'), Some('1'), None]);
+        let five = Constant::Integer(5);
+        let bits: Vec<Option<char>> = (0..4).map(|i| five.bit_at(i)).collect();
+        assert_eq!(bits, [Some('1'), Some('0'), Some('1'), Some('0')]);
+        assert_eq!(five.bit_at(31), Some('0'));
+        assert_eq!(five.bit_at(32), None);
+        assert_eq!(Constant::String("a".to_string()).bit_at(0), None);
+        let fill = Constant::Fill('1', 3);
+        assert_eq!((fill.bit_at(2), fill.bit_at(3)), (Some('1'), None));
+        assert_eq!(fill.as_i64(), Some(7));
+        assert_eq!(fill.kind(), ConstantKind::Fill);
+    }
+
     #[test]
     fn test_constant() {
         let vectors = [
@@ -48,4 +252,63 @@ mod tests {
             assert_eq!(ret.1, *expected);
         }
     }
+
+    #[test]
+    fn test_binary_digit_case() {
+        // uppercase digits are read lowercase
+        let span = Span::new_extra("4'XZ01", Default::default());
+        assert_eq!(
+            constant(span).unwrap().1,
+            Constant::Value(vec!['1', '0', 'z', 'x'])
+        );
+        let span = Span::new_extra("4'xz01", Default::default());
+        assert_eq!(
+            constant(span).unwrap().1,
+            Constant::Value(vec!['1', '0', 'z', 'x'])
+        );
+    }
+
+    #[test]
+    fn test_constant_real() {
+        let vectors = [
+            ("10.0", Constant::Real("10.0".to_string())),
+            ("-0.5E-3", Constant::Real("-0.5E-3".to_string())),
+            ("1.25e+2", Constant::Real("1.25e+2".to_string())),
+        ];
+        for (input, expected) in vectors {
+            let span = Span::new_extra(input, Default::default());
+            assert_eq!(constant(span).unwrap().1, expected, "failed: {}", input);
+        }
+    }
+
+    #[test]
+    fn test_from_i64_width() {
+        let bits = |s: &str| Constant::Value(s.chars().rev().collect());
+        assert_eq!(Constant::from_i64_width(10, 4), bits("1010"));
+        assert_eq!(Constant::from_i64_width(10, 6), bits("001010"));
+        assert_eq!(Constant::from_i64_width(0, 0), bits(""));
+        // negatives are stored in 2's complement
+        assert_eq!(Constant::from_i64_width(-6, 4), bits("1010"));
+        assert_eq!(Constant::from_i64_width(-1, 66), bits(&"1".repeat(66)));
+        assert_eq!(
+            Constant::from_i64_width(i64::MIN, 65),
+            bits(&format!("11{}", "0".repeat(63)))
+        );
+        // values that do not fit are truncated
+        assert_eq!(Constant::from_i64_width(0b11010, 3), bits("010"));
+        assert_eq!(Constant::from_i64_width(-9, 3), bits("111"));
+    }
+
+    #[test]
+    fn test_as_i64() {
+        for value in [0, 1, 10, 255, i64::MAX] {
+            assert_eq!(Constant::from_i64_width(value, 64).as_i64(), Some(value));
+        }
+        // leading zeros beyond 64 bits are fine, set bits are not
+        assert_eq!(Constant::from_i64_width(5, 100).as_i64(), Some(5));
+        assert_eq!(Constant::from_i64_width(-1, 64).as_i64(), None);
+        assert_eq!(Constant::from_i64_width(-1, 4).as_i64(), Some(15));
+        assert_eq!(Constant::Value(vec!['1', 'z']).as_i64(), None);
+        assert_eq!(Constant::String("1".to_string()).as_i64(), None);
+    }
 }
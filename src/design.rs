@@ -3,33 +3,410 @@
 //! `<file> ::= <autoidx-stmt>? <module>*`
 //!
 
-use crate::{characters, string, value, Design, Span};
+use crate::*;
 use nom::{
     bytes::complete::tag,
     combinator::opt,
     multi::{many0, many1},
-    IResult,
 };
 use nom_tracable::tracable_parser;
+use std::collections::{HashMap, HashSet};
 
 impl Design {
+    /// Every distinct constant of the design, found in attributes, parameters and sigspecs
+    pub fn constants(&self) -> HashSet<Constant> {
+        let mut constants: Vec<&Constant> = vec![];
+        let mut sigspecs: Vec<&SigSpec> = vec![];
+        for module in self.modules.values() {
+            constants.extend(module.attributes.values());
+            constants.extend(module.parameters.values().flatten());
+            module
+                .wires
+                .values()
+                .for_each(|w| constants.extend(w.attributes.values()));
+            module
+                .memories
+                .values()
+                .for_each(|m| constants.extend(m.attributes.values()));
+            for cell in module.cells.values() {
+                constants.extend(cell.attributes.values());
+                constants.extend(cell.parameters.values());
+                sigspecs.extend(cell.connections.values());
+            }
+            for process in module.processes.values() {
+                constants.extend(process.attributes.values());
+                sigspecs.extend(process.assignments.iter().flat_map(|(d, s)| [d, s]));
+                let mut switches: Vec<&Switch> = process.switches.iter().collect();
+                while let Some(switch) = switches.pop() {
+                    constants.extend(switch.attributes.values());
+                    sigspecs.push(&switch.switch_on_sigspec);
+                    for case in &switch.cases {
+                        constants.extend(case.attributes.values());
+                        sigspecs.extend(case.compare_against.iter().flatten());
+                        for body in &case.case_bodies {
+                            match body {
+                                CaseBody::Switch(switch) => switches.push(switch),
+                                CaseBody::Assign((d, s)) => sigspecs.extend([d, s]),
+                            }
+                        }
+                    }
+                }
+                for sync in &process.syncs {
+                    if let SyncOn::Signal(_, signal) = &sync.sync_event {
+                        sigspecs.push(signal);
+                    }
+                    for update in &sync.updates {
+                        constants.extend(update.attributes.values());
+                        sigspecs.extend([&update.dest, &update.src]);
+                    }
+                    for memwr in sync.memwrs.values() {
+                        constants.extend(memwr.attributes.values());
+                        sigspecs.extend([
+                            &memwr.address,
+                            &memwr.data,
+                            &memwr.enable,
+                            &memwr.priority_mask,
+                        ]);
+                    }
+                }
+            }
+            sigspecs.extend(module.connections.iter().flat_map(|(d, s)| [d, s]));
+        }
+        for sigspec in sigspecs {
+            sigspec.walk(&mut |s| {
+                if let SigSpec::Constant(constant) = s {
+                    constants.push(constant);
+                }
+            });
+        }
+        constants.into_iter().cloned().collect()
+    }
+
+    /// Count the modules, wires, cells, memories and processes of the design
+    pub fn statistics(&self) -> DesignStats {
+        let mut stats = DesignStats {
+            modules: self.modules.len(),
+            top: self.top_module().map(str::to_string),
+            wires: self.wire_count(),
+            cells: self.cell_count(),
+            memories: self.memory_count(),
+            processes: self.process_count(),
+            ..Default::default()
+        };
+        for module in self.modules.values() {
+            for cell in module.cells.values() {
+                *stats.cell_types.entry(cell.cell_type.clone()).or_default() += 1;
+            }
+        }
+        stats
+    }
+
+    /// The number of cells, across all modules
+    ///
+    /// ```
+    /// let src = "module \\a\n  wire \\w\n  cell $not $n\n  end\nend\n\
+    ///            module \\b\n  wire \\x\n  wire \\y\n  memory \\m\n  process $p\n  end\nend\n";
+    /// let design = rtlicious::parse(src).unwrap();
+    /// assert_eq!(design.cell_count(), 1);
+    /// assert_eq!(design.wire_count(), 3);
+    /// assert_eq!(design.memory_count(), 1);
+    /// assert_eq!(design.process_count(), 1);
+    /// ```
+    pub fn cell_count(&self) -> usize {
+        self.modules.values().map(|module| module.cells.len()).sum()
+    }
+
+    /// The number of wires, across all modules
+    pub fn wire_count(&self) -> usize {
+        self.modules.values().map(|module| module.wires.len()).sum()
+    }
+
+    /// The number of memories, across all modules
+    pub fn memory_count(&self) -> usize {
+        self.modules
+            .values()
+            .map(|module| module.memories.len())
+            .sum()
+    }
+
+    /// The number of processes, across all modules
+    pub fn process_count(&self) -> usize {
+        self.modules
+            .values()
+            .map(|module| module.processes.len())
+            .sum()
+    }
+
+    /// Every cell of the design, as `(module, name, cell)`, sorted by module then cell name
+    pub fn all_cells(&self) -> Vec<(&str, &str, &Cell)> {
+        let mut cells: Vec<(&str, &str, &Cell)> = self
+            .modules
+            .iter()
+            .flat_map(|(module_name, module)| {
+                module
+                    .cells
+                    .iter()
+                    .map(move |(name, cell)| (module_name.as_str(), name.as_str(), cell))
+            })
+            .collect();
+        cells.sort_by_key(|(module, name, _)| (*module, *name));
+        cells
+    }
+
+    /// The cells for which `f(module, name, cell)` holds, in the order of `all_cells`
+    ///
+    /// ```
+    /// use rtlicious::Constant;
+    ///
+    /// let src = "module \\m\n  cell $not $a\n    parameter \\Y_WIDTH 2\n  end\n  \
+    ///            cell $not $b\n    parameter \\Y_WIDTH 1\n  end\nend\n";
+    /// let design = rtlicious::parse(src).unwrap();
+    /// let wide = design.find_cells(|_, _, cell| {
+    ///     cell.parameters().get("Y_WIDTH") == Some(&Constant::Integer(2))
+    /// });
+    /// let names: Vec<(&str, &str)> = wide.iter().map(|(module, name, _)| (*module, *name)).collect();
+    /// assert_eq!(names, [("m", "a")]);
+    /// ```
+    pub fn find_cells(&self, f: impl Fn(&str, &str, &Cell) -> bool) -> Vec<(&str, &str, &Cell)> {
+        self.all_cells()
+            .into_iter()
+            .filter(|(module, name, cell)| f(module, name, cell))
+            .collect()
+    }
+
+    /// The names of the modules with a true `top` attribute, sorted
+    fn tops(&self) -> Vec<&str> {
+        let mut tops: Vec<&str> = self
+            .modules
+            .iter()
+            .filter(|(_, module)| module.attribute_bool("top") == Some(true))
+            .map(|(name, _)| name.as_str())
+            .collect();
+        tops.sort();
+        tops
+    }
+
+    /// The module with the `top` attribute, the first by name if several have it
+    pub fn top_module(&self) -> Option<&str> {
+        self.tops().first().copied()
+    }
+
+    /// The name of the top module, if exactly one module has the `top` attribute
+    pub fn verify_single_top(&self) -> Result<&str, TopError> {
+        match self.tops().as_slice() {
+            [] => Err(TopError::NoTop),
+            [top] => Ok(top),
+            tops => Err(TopError::MultipleTops(
+                tops.iter().map(|top| top.to_string()).collect(),
+            )),
+        }
+    }
+
+    /// The modules each module instantiates, ie. its cells whose type is a module of the design
+    ///
+    /// Every module is a key, the instantiated names are sorted and deduplicated.
+    pub fn instantiation_graph(&self) -> HashMap<String, Vec<String>> {
+        self.modules
+            .iter()
+            .map(|(name, module)| {
+                let mut children: Vec<String> = module
+                    .cells()
+                    .values()
+                    .map(|cell| cell.cell_type())
+                    .filter(|cell_type| self.modules.contains_key(*cell_type))
+                    .cloned()
+                    .collect();
+                children.sort();
+                children.dedup();
+                (name.clone(), children)
+            })
+            .collect()
+    }
+
+    /// Whether no module instantiates itself, directly or through its submodules
+    pub fn is_acyclic(&self) -> bool {
+        #[derive(Clone, Copy, PartialEq)]
+        enum Visit {
+            InProgress,
+            Done,
+        }
+        let graph = self.instantiation_graph();
+        let mut visits: HashMap<&str, Visit> = HashMap::new();
+        for root in graph.keys() {
+            if visits.contains_key(root.as_str()) {
+                continue;
+            }
+            // iterative depth first search, holding the index of the next child to visit
+            let mut stack = vec![(root.as_str(), 0)];
+            visits.insert(root, Visit::InProgress);
+            while let Some((name, next)) = stack.pop() {
+                let Some(child) = graph[name].get(next) else {
+                    visits.insert(name, Visit::Done);
+                    continue;
+                };
+                stack.push((name, next + 1));
+                match visits.get(child.as_str()) {
+                    Some(Visit::InProgress) => return false,
+                    Some(Visit::Done) => {}
+                    None => {
+                        visits.insert(child, Visit::InProgress);
+                        stack.push((child, 0));
+                    }
+                }
+            }
+        }
+        true
+    }
+
+    /// Remove the module `name`, leaving the cells instantiating it in place
+    pub fn remove_module(&mut self, name: &str) -> Option<Module> {
+        self.modules.remove(name)
+    }
+
+    /// Remove the modules that `top` does not instantiate, directly or through its submodules,
+    /// returning their names sorted
+    ///
+    /// Nothing is removed if there is no module `top`.
+    pub fn remove_unreachable(&mut self, top: &str) -> Vec<String> {
+        if !self.modules.contains_key(top) {
+            return vec![];
+        }
+        let graph = self.instantiation_graph();
+        let mut reachable: HashSet<&str> = HashSet::from([top]);
+        let mut stack = vec![top];
+        while let Some(name) = stack.pop() {
+            for child in &graph[name] {
+                if reachable.insert(child) {
+                    stack.push(child);
+                }
+            }
+        }
+        let mut removed: Vec<String> = graph
+            .keys()
+            .filter(|name| !reachable.contains(name.as_str()))
+            .cloned()
+            .collect();
+        removed.sort();
+        for name in &removed {
+            self.modules.remove(name);
+        }
+        removed
+    }
+
+    /// Take the modules out of the design, by name
+    pub fn into_modules(self) -> HashMap<String, Module> {
+        self.modules
+    }
+
     /// Parse a string into a `Design` struct
-    pub fn new_from_str(input: &str) -> Result<Design, Span> {
-        let input = Span::new_extra(input, Default::default());
-        let res = design(input);
-        match res {
-            Ok((rem, design)) => {
-                if rem.fragment().is_empty() {
-                    Ok(design)
-                } else {
-                    Err(rem)
+    pub fn new_from_str(input: &str) -> Result<Design, ParseError> {
+        Self::new_from_str_with(input, &ParseOptions::default())
+    }
+
+    /// Get a module by name to edit it
+    pub fn module_mut(&mut self, name: &str) -> Option<&mut Module> {
+        self.modules.get_mut(name)
+    }
+
+    /// Rename the module `old` to `new`, along with the type of every cell instantiating it
+    pub fn rename_module(&mut self, old: &str, new: &str) -> Result<(), RenameError> {
+        if !self.modules.contains_key(old) {
+            return Err(RenameError::NotFound(old.to_string()));
+        }
+        if old == new {
+            return Ok(());
+        }
+        if self.modules.contains_key(new) {
+            return Err(RenameError::AlreadyExists(new.to_string()));
+        }
+        let module = self.modules.remove(old).unwrap();
+        self.modules.insert(new.to_string(), module);
+        for module in self.modules.values_mut() {
+            for cell in module.cells.values_mut() {
+                if cell.cell_type == old {
+                    cell.cell_type = new.to_string();
                 }
             }
-            Err(e) => {
-                dbg!(&e);
-                Err(input)
+        }
+        Ok(())
+    }
+
+    /// Parse several designs written one after the other, ie. concatenated `write_rtlil` outputs
+    ///
+    /// A new design starts at an `autoidx` statement or a `# Generated by Yosys` header line, as
+    /// soon as the current one holds more than comments. The line of an error is counted from the
+    /// start of the whole input.
+    pub fn new_multi_from_str(input: &str) -> Result<Vec<Design>, ParseError> {
+        // (start offset, start line, holds more than comments)
+        let mut chunks = vec![(0, 1, false)];
+        let mut offset = 0;
+        for (index, line) in input.split_inclusive('\n').enumerate() {
+            let trimmed = line.trim();
+            let boundary = trimmed.starts_with("# Generated by Yosys")
+                || trimmed.split_whitespace().next() == Some("autoidx");
+            if boundary && chunks.last().is_some_and(|chunk| chunk.2) {
+                chunks.push((offset, index + 1, false));
+            }
+            if !trimmed.is_empty() && !trimmed.starts_with('#') {
+                chunks.last_mut().unwrap().2 = true;
             }
+            offset += line.len();
         }
+        let ends = chunks
+            .iter()
+            .skip(1)
+            .map(|chunk| chunk.0)
+            .chain([input.len()]);
+        chunks
+            .iter()
+            .zip(ends)
+            .filter(|((_, _, content), _)| *content)
+            .map(|(&(start, line, _), end)| {
+                Design::new_from_str(&input[start..end]).map_err(|mut e| {
+                    e.shift_lines(line as u32 - 1);
+                    e
+                })
+            })
+            .collect()
+    }
+
+    /// Parse a string into a `Design` struct with the given options
+    pub fn new_from_str_with(input: &str, options: &ParseOptions) -> Result<Design, ParseError> {
+        options.scope(|| crate::error::parse_complete(input, design))
+    }
+}
+
+/// A design of the modules, by name, without `autoidx`
+///
+/// ```
+/// use rtlicious::Design;
+///
+/// let parsed = rtlicious::parse("module \\a\nend\nmodule \\b\nend\n").unwrap();
+/// let mut design: Design = parsed
+///     .into_modules()
+///     .into_iter()
+///     .filter(|(name, _)| name == "a")
+///     .collect();
+/// assert_eq!(*design.autoidx(), None);
+/// let other = rtlicious::parse("module \\c\nend\n").unwrap();
+/// design.extend(other.into_modules());
+/// let mut names: Vec<&String> = design.modules().keys().collect();
+/// names.sort();
+/// assert_eq!(names, ["a", "c"]);
+/// ```
+impl FromIterator<(String, Module)> for Design {
+    fn from_iter<I: IntoIterator<Item = (String, Module)>>(iter: I) -> Self {
+        Design {
+            autoidx: None,
+            modules: iter.into_iter().collect(),
+        }
+    }
+}
+
+/// Add the modules, replacing those of the same name
+impl Extend<(String, Module)> for Design {
+    fn extend<I: IntoIterator<Item = (String, Module)>>(&mut self, iter: I) {
+        self.modules.extend(iter);
     }
 }
 
@@ -67,6 +444,39 @@ pub fn autoidx_stmt(input: Span) -> IResult<Span, i32> {
 mod tests {
     use super::*;
     use indoc::indoc;
+
+    const COMB_NOT1: &str = indoc! {r#"
+        # Generated by Yosys 0.40 (git sha1 a1bb0255d65, clang++ 15.0.0 -fPIC -Os)
+        autoidx 3
+        attribute \top 1
+        attribute \src "vectors/comb_not1.v:2.1-9.10"
+        module \comb_not1
+          attribute \src "vectors/comb_not1.v:6.5-8.8"
+          wire $0\b[0:0]
+          attribute \src "vectors/comb_not1.v:7.13-7.15"
+          wire $logic_not$vectors/comb_not1.v:7$2_Y
+          attribute \src "vectors/comb_not1.v:3.11-3.12"
+          wire input 1 \a
+          attribute \src "vectors/comb_not1.v:4.12-4.13"
+          wire output 2 \b
+          attribute \src "vectors/comb_not1.v:7.13-7.15"
+          cell $logic_not $logic_not$vectors/comb_not1.v:7$2
+            parameter \A_SIGNED 0
+            parameter \A_WIDTH 1
+            parameter \Y_WIDTH 1
+            connect \A \a
+            connect \Y $logic_not$vectors/comb_not1.v:7$2_Y
+          end
+          attribute \src "vectors/comb_not1.v:6.5-8.8"
+          process $proc$vectors/comb_not1.v:6$1
+            assign { } { }
+            assign $0\b[0:0] $logic_not$vectors/comb_not1.v:7$2_Y
+            sync always
+              update \b $0\b[0:0]
+          end
+        end
+    "#};
+
     #[test]
     fn test_file() {
         let input = indoc! {r#"
@@ -103,6 +513,33 @@ mod tests {
         let res = Design::new_from_str(input).unwrap();
         assert!(!res.modules.is_empty());
     }
+
+    #[test]
+    fn test_statistics() {
+        let res = Design::new_from_str(COMB_NOT1).unwrap();
+        let stats = res.statistics();
+        assert_eq!(stats.modules, 1);
+        assert_eq!(stats.top.as_deref(), Some("comb_not1"));
+        assert_eq!(stats.wires, 4);
+        assert_eq!(stats.cells, 1);
+        assert_eq!(stats.memories, 0);
+        assert_eq!(stats.processes, 1);
+        assert_eq!(
+            stats.cell_types,
+            HashMap::from([("logic_not".to_string(), 1)])
+        );
+    }
+
+    #[test]
+    fn test_file_without_trailing_newline() {
+        let input = "autoidx 1\nmodule \\a\n  wire $b\nend\nmodule \\c\nend";
+        assert!(input.ends_with('d'));
+        let res = Design::new_from_str(input).unwrap();
+        assert_eq!(res.modules.len(), 2);
+        let res = Design::new_from_str("module \\a\nend # module \\a").unwrap();
+        assert_eq!(res.modules.len(), 1);
+    }
+
     #[test]
     fn test_autoidx_stmt() {
         let vectors = vec![
@@ -124,4 +561,271 @@ mod tests {
             assert_eq!(ret.1, expected);
         }
     }
+
+    #[test]
+    fn test_instantiation_graph() {
+        let input = indoc! {r#"
+            module \top
+              cell \mid $m0
+              end
+              cell \mid $m1
+              end
+              cell $add $a
+              end
+            end
+            module \mid
+              cell \leaf $l
+              end
+            end
+            module \leaf
+            end
+        "#};
+        let design = Design::new_from_str(input).unwrap();
+        let graph = design.instantiation_graph();
+        assert_eq!(graph.len(), 3);
+        assert_eq!(graph["top"], vec!["mid".to_string()]);
+        assert_eq!(graph["mid"], vec!["leaf".to_string()]);
+        assert!(graph["leaf"].is_empty());
+        assert!(design.is_acyclic());
+    }
+
+    #[test]
+    fn test_is_acyclic() {
+        let input = indoc! {r#"
+            module \top
+              cell \a $a
+              end
+            end
+            module \a
+              cell \b $b
+              end
+            end
+            module \b
+              cell \a $a
+              end
+            end
+        "#};
+        assert!(!Design::new_from_str(input).unwrap().is_acyclic());
+        let input = "module \\a\n  cell \\a $a\n  end\nend\n";
+        assert!(!Design::new_from_str(input).unwrap().is_acyclic());
+    }
+
+    #[test]
+    fn test_new_multi_from_str() {
+        let input = indoc! {r#"
+            # Generated by Yosys 0.40
+            autoidx 3
+            module \a
+            end
+            # Generated by Yosys 0.40
+            autoidx 7
+            module \b
+            end
+            autoidx 9
+            module \c
+            end
+            module \d
+            end
+        "#};
+        let designs = Design::new_multi_from_str(input).unwrap();
+        assert_eq!(designs.len(), 3);
+        assert_eq!(designs[0].autoidx, Some(3));
+        assert!(designs[0].modules.contains_key("a"));
+        assert_eq!(designs[1].autoidx, Some(7));
+        assert!(designs[1].modules.contains_key("b"));
+        assert_eq!(designs[2].modules.len(), 2);
+        assert!(Design::new_multi_from_str("\n# nothing\n")
+            .unwrap()
+            .is_empty());
+
+        let input = "autoidx 1\nmodule \\a\nend\nautoidx 2\nmodule \\b\n  foo\nend\n";
+        let e = Design::new_multi_from_str(input).unwrap_err();
+        assert_eq!(*e.line(), 6);
+        assert_eq!(e.snippet(), "  foo");
+    }
+
+    #[test]
+    fn test_verify_single_top() {
+        let design = |tops: &[&str]| {
+            let mut input = String::new();
+            for name in ["a", "b", "c"] {
+                if tops.contains(&name) {
+                    input.push_str("attribute \\top 1\n");
+                }
+                input.push_str(&format!("module \\{}\nend\n", name));
+            }
+            Design::new_from_str(&input).unwrap()
+        };
+        assert_eq!(design(&[]).verify_single_top(), Err(TopError::NoTop));
+        assert_eq!(design(&[]).top_module(), None);
+        assert_eq!(design(&["b"]).verify_single_top(), Ok("b"));
+        let e = design(&["c", "a"]).verify_single_top().unwrap_err();
+        assert_eq!(e, TopError::MultipleTops(vec!["a".into(), "c".into()]));
+        assert_eq!(
+            e.to_string(),
+            "several modules have the top attribute: a, c"
+        );
+        assert_eq!(design(&["c", "a"]).top_module(), Some("a"));
+
+        let top_0 = Design::new_from_str("attribute \\top 0\nmodule \\a\nend\n").unwrap();
+        assert_eq!(top_0.verify_single_top(), Err(TopError::NoTop));
+    }
+
+    #[test]
+    fn test_remove_unreachable() {
+        let input = indoc! {r#"
+            module \top
+              cell \mid $m
+              end
+            end
+            module \mid
+              cell \leaf $l
+              end
+            end
+            module \leaf
+            end
+            module \unused
+              cell \leaf $l
+              end
+            end
+            module \other
+            end
+        "#};
+        let mut design = Design::new_from_str(input).unwrap();
+        assert!(design.remove_unreachable("missing").is_empty());
+        assert_eq!(design.modules().len(), 5);
+        assert_eq!(design.remove_unreachable("top"), ["other", "unused"]);
+        let mut names: Vec<&String> = design.modules().keys().collect();
+        names.sort();
+        assert_eq!(names, ["leaf", "mid", "top"]);
+
+        assert!(design.remove_module("leaf").is_some());
+        assert!(design.remove_module("leaf").is_none());
+        // the cell instantiating it is now a cell of an unknown type
+        assert_eq!(design.remove_unreachable("top"), Vec::<String>::new());
+        assert_eq!(design.modules()["mid"].cells().len(), 1);
+    }
+
+    #[test]
+    fn test_constants() {
+        let res = Design::new_from_str(COMB_NOT1).unwrap();
+        let constants = res.constants();
+        assert_eq!(constants.len(), 7);
+        assert!(constants.contains(&Constant::Integer(0)));
+        assert!(constants.contains(&Constant::Integer(1)));
+        assert!(constants.contains(&Constant::String(
+            "vectors/comb_not1.v:7.13-7.15".to_string()
+        )));
+    }
+
+    #[test]
+    fn test_constants_update_attributes() {
+        let input = indoc! {r#"
+            module \a
+              wire \q
+              process $p
+                sync always
+                  attribute \src "a.v:3"
+                  update \q 1'1
+              end
+            end
+        "#};
+        let design = Design::new_from_str(input).unwrap();
+        assert_eq!(
+            design.constants(),
+            HashSet::from([
+                Constant::String("a.v:3".to_string()),
+                Constant::Value(vec!['1'])
+            ])
+        );
+    }
+
+    #[test]
+    fn test_find_cells() {
+        let input = indoc! {r#"
+            module \b
+              cell $add $y
+                connect \A \x
+              end
+              cell $not $x
+              end
+            end
+            module \a
+              cell \b $u
+              end
+            end
+        "#};
+        let design = Design::new_from_str(input).unwrap();
+        let names = |cells: Vec<(&str, &str, &Cell)>| -> Vec<(String, String)> {
+            cells
+                .into_iter()
+                .map(|(module, name, _)| (module.to_string(), name.to_string()))
+                .collect()
+        };
+        assert_eq!(
+            names(design.all_cells()),
+            [("a", "u"), ("b", "x"), ("b", "y")].map(|(m, n)| (m.to_string(), n.to_string()))
+        );
+        let driven_by_x = design.find_cells(|_, _, cell| {
+            cell.connections()
+                .values()
+                .any(|signal| signal == &SigSpec::wire("x"))
+        });
+        assert_eq!(names(driven_by_x), [("b".to_string(), "y".to_string())]);
+        assert!(design.find_cells(|module, _, _| module == "c").is_empty());
+    }
+
+    #[test]
+    fn test_into_modules() {
+        let input = indoc! {r#"
+            module \A
+              cell $add $c
+              end
+            end
+            module \B
+            end
+        "#};
+        let mut modules = Design::new_from_str(input).unwrap().into_modules();
+        assert_eq!(modules.len(), 2);
+        let mut a = modules.remove("A").unwrap();
+        let add = a.remove_cell("c").unwrap();
+        a.insert_cell("d", add);
+        assert_eq!(a.cells().keys().collect::<Vec<_>>(), vec!["d"]);
+        assert!(modules.contains_key("B"));
+    }
+
+    #[test]
+    fn test_rename_module() {
+        let input = indoc! {r#"
+            module \A
+              cell \B $b
+              end
+              cell $add $c
+              end
+            end
+            module \B
+            end
+            module \C
+            end
+        "#};
+        let mut design = Design::new_from_str(input).unwrap();
+        design.rename_module("B", "D").unwrap();
+        assert!(!design.modules.contains_key("B"));
+        assert!(design.modules.contains_key("D"));
+        let cells = design.modules["A"].cells();
+        assert_eq!(cells["b"].cell_type(), "D");
+        assert_eq!(cells["c"].cell_type(), "add");
+        assert_eq!(design.instantiation_graph()["A"], vec!["D".to_string()]);
+
+        assert_eq!(
+            design.rename_module("D", "C"),
+            Err(RenameError::AlreadyExists("C".to_string()))
+        );
+        assert_eq!(
+            design.rename_module("B", "E"),
+            Err(RenameError::NotFound("B".to_string()))
+        );
+        assert_eq!(design.rename_module("D", "D"), Ok(()));
+        assert_eq!(design.modules.len(), 3);
+    }
 }
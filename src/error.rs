@@ -0,0 +1,384 @@
+//! Error types returned by the library.
+
+use std::{fmt, sync::Arc};
+
+use nom::error::ErrorKind;
+
+use crate::{
+    CycleError, Diagnostic, IResult, NomError, ParseError, PassError, RenameError, Span, TopError,
+    ValidationError,
+};
+
+/// Run `parser` over the whole of `input`, anything left over is an error
+pub(crate) fn parse_complete<T>(
+    input: &str,
+    mut parser: impl FnMut(Span) -> IResult<Span, T>,
+) -> Result<T, ParseError> {
+    let span = Span::new_extra(input, Default::default());
+    match parser(span) {
+        Ok((rem, parsed)) => {
+            if rem.fragment().is_empty() {
+                Ok(parsed)
+            } else {
+                Err(ParseError::new(input, rem))
+            }
+        }
+        Err(nom::Err::Error(e)) | Err(nom::Err::Failure(e)) => {
+            log::debug!("{:?}", e);
+            Err(ParseError::from_nom(input, e))
+        }
+        Err(nom::Err::Incomplete(_)) => Err(ParseError::new(input, span)),
+    }
+}
+
+impl ParseError {
+    /// Locate the failing span `at` within the parsed `input`
+    pub(crate) fn new(input: &str, at: Span) -> Self {
+        let offset = at.location_offset();
+        let start = input[..offset].rfind(['\n', '\r']).map_or(0, |i| i + 1);
+        let end = input[offset..]
+            .find(['\n', '\r'])
+            .map_or(input.len(), |i| offset + i);
+        Self {
+            line: at.location_line(),
+            column: at.get_utf8_column(),
+            snippet: input[start..end].to_string(),
+            nom_kind: None,
+            #[cfg(feature = "debug-errors")]
+            stack: vec![],
+        }
+    }
+
+    #[cfg(not(feature = "debug-errors"))]
+    fn from_nom(input: &str, e: NomError<Span>) -> Self {
+        Self {
+            nom_kind: Some(e.code),
+            ..Self::new(input, e.input)
+        }
+    }
+
+    #[cfg(feature = "debug-errors")]
+    fn from_nom(input: &str, e: NomError<Span>) -> Self {
+        use nom::error::VerboseErrorKind;
+
+        let Some((at, _)) = e.errors.first() else {
+            return Self::new(input, Span::new_extra(input, Default::default()));
+        };
+        Self {
+            nom_kind: e.errors.iter().find_map(|(_, kind)| match kind {
+                VerboseErrorKind::Nom(kind) => Some(*kind),
+                VerboseErrorKind::Char(_) => Some(ErrorKind::Char),
+                VerboseErrorKind::Context(_) => None,
+            }),
+            stack: e
+                .errors
+                .iter()
+                .map(|(at, kind)| {
+                    (
+                        at.location_line(),
+                        at.get_utf8_column(),
+                        format!("{:?}", kind),
+                    )
+                })
+                .collect(),
+            ..Self::new(input, *at)
+        }
+    }
+
+    /// The kind of the innermost `nom` parser that failed, if the parsers failed
+    ///
+    /// This is `None` when the design was parsed but some input was left over.
+    pub fn nom_kind(&self) -> Option<ErrorKind> {
+        self.nom_kind
+    }
+
+    /// Count lines from `by` lines earlier, for an input parsed from the middle of a file
+    pub(crate) fn shift_lines(&mut self, by: u32) {
+        self.line += by;
+        #[cfg(feature = "debug-errors")]
+        self.stack.iter_mut().for_each(|(line, _, _)| *line += by);
+    }
+
+    /// A line pointing at the failing column of `snippet` with a `^`
+    pub fn caret(&self) -> String {
+        // keep the tabs of the snippet so the caret lines up
+        let mut caret: String = self
+            .snippet
+            .chars()
+            .take(self.column - 1)
+            .map(|c| if c == '\t' { '\t' } else { ' ' })
+            .collect();
+        caret.push('^');
+        caret
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "could not parse the element starting at line {}, column {}:",
+            self.line, self.column
+        )?;
+        writeln!(f, "{}", self.snippet)?;
+        write!(f, "{}", self.caret())
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "line {}, column {}: {}",
+            self.line, self.column, self.message
+        )
+    }
+}
+
+impl fmt::Display for RenameError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RenameError::NotFound(name) => write!(f, "no module named {}", name),
+            RenameError::AlreadyExists(name) => write!(f, "a module named {} already exists", name),
+        }
+    }
+}
+
+impl std::error::Error for RenameError {}
+
+impl fmt::Display for TopError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TopError::NoTop => write!(f, "no module has the top attribute"),
+            TopError::MultipleTops(names) => {
+                write!(
+                    f,
+                    "several modules have the top attribute: {}",
+                    names.join(", ")
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for TopError {}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ValidationError::UnknownWire(name) => write!(f, "no wire named {}", name),
+            ValidationError::RangeOutOfBounds { wire, index } => {
+                write!(f, "bit {} is beyond the end of wire {}", index, wire)
+            }
+            ValidationError::WidthMismatch { index, dest, src } => write!(
+                f,
+                "connection {} drives {} bits from {} bits",
+                index, dest, src
+            ),
+            ValidationError::InitWidthMismatch { wire, width, init } => write!(
+                f,
+                "wire {} is {} bits wide but its initial value has {} bits",
+                wire, width, init
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ValidationError {}
+
+impl PassError {
+    /// An error with the given message
+    pub fn new(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+            source: None,
+        }
+    }
+
+    /// An error with the given message, caused by `source`
+    ///
+    /// ```
+    /// use std::error::Error;
+    /// use rtlicious::{PassError, ValidationError};
+    ///
+    /// let cause = ValidationError::UnknownWire("a".to_string());
+    /// let e = PassError::with_source("module m is not valid", cause);
+    /// assert_eq!(e.to_string(), "module m is not valid");
+    /// assert_eq!(e.source().unwrap().to_string(), "no wire named a");
+    /// ```
+    pub fn with_source(
+        message: impl Into<String>,
+        source: impl std::error::Error + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            message: message.into(),
+            source: Some(Arc::new(source)),
+        }
+    }
+}
+
+/// Errors are equal with the same message and sources that display the same
+impl PartialEq for PassError {
+    fn eq(&self, other: &Self) -> bool {
+        self.message == other.message
+            && self.source.as_ref().map(ToString::to_string)
+                == other.source.as_ref().map(ToString::to_string)
+    }
+}
+
+impl Eq for PassError {}
+
+impl fmt::Display for PassError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl std::error::Error for PassError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.source
+            .as_deref()
+            .map(|source| source as &(dyn std::error::Error + 'static))
+    }
+}
+
+impl CycleError {
+    pub(crate) fn new(cells: Vec<String>) -> Self {
+        Self { cells }
+    }
+}
+
+impl fmt::Display for CycleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "cells depend on each other in a loop: {}",
+            self.cells.join(", ")
+        )
+    }
+}
+
+impl std::error::Error for CycleError {}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use indoc::indoc;
+    use nom::error::ErrorKind;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_parse_error() {
+        let input = indoc! {r#"
+            module \a
+              wire $b
+              wire foo $c
+              wire $d
+            end
+        "#};
+        let e = parse(input).unwrap_err();
+        assert_eq!(*e.line(), 3);
+        assert_eq!(*e.column(), 3);
+        assert_eq!(e.snippet(), "  wire foo $c");
+        assert_eq!(e.caret(), "  ^");
+        assert_eq!(
+            e.to_string(),
+            "could not parse the element starting at line 3, column 3:\n  wire foo $c\n  ^"
+        );
+    }
+
+    #[test]
+    fn test_parse_error_trailing() {
+        let input = "module \\a\nend\nfoo\n";
+        let e = parse(input).unwrap_err();
+        assert_eq!(*e.line(), 3);
+        assert_eq!(e.snippet(), "foo");
+        assert_eq!(e.nom_kind(), None);
+    }
+
+    #[test]
+    fn test_nom_kind() {
+        let input = indoc! {r#"
+            module \a
+              wire $b
+              connect $b 3'10
+            end
+        "#};
+        let e = parse(input).unwrap_err();
+        assert_eq!(*e.line(), 3);
+        assert_eq!(*e.column(), 14);
+        assert_eq!(e.nom_kind(), Some(ErrorKind::Verify));
+    }
+
+    #[test]
+    fn test_error_messages() {
+        let errors: Vec<Box<dyn std::error::Error>> = vec![
+            Box::new(parse("foo").unwrap_err()),
+            Box::new(RenameError::NotFound("a".to_string())),
+            Box::new(RenameError::AlreadyExists("a".to_string())),
+            Box::new(TopError::NoTop),
+            Box::new(TopError::MultipleTops(vec![
+                "a".to_string(),
+                "b".to_string(),
+            ])),
+            Box::new(ValidationError::UnknownWire("a".to_string())),
+            Box::new(ValidationError::WidthMismatch {
+                index: 0,
+                dest: 2,
+                src: 1,
+            }),
+            Box::new(ValidationError::InitWidthMismatch {
+                wire: "a".to_string(),
+                width: 2,
+                init: 1,
+            }),
+            Box::new(PassError::new("no")),
+            Box::new(CycleError::new(vec!["a".to_string()])),
+        ];
+        let messages: Vec<String> = errors.iter().map(ToString::to_string).collect();
+        assert_eq!(
+            messages,
+            [
+                "could not parse the element starting at line 1, column 1:\nfoo\n^",
+                "no module named a",
+                "a module named a already exists",
+                "no module has the top attribute",
+                "several modules have the top attribute: a, b",
+                "no wire named a",
+                "connection 0 drives 2 bits from 1 bits",
+                "wire a is 2 bits wide but its initial value has 1 bits",
+                "no",
+                "cells depend on each other in a loop: a",
+            ]
+        );
+        assert!(errors.iter().all(|e| e.source().is_none()));
+    }
+
+    #[test]
+    fn test_pass_error_source() {
+        use std::error::Error;
+
+        let cause = CycleError::new(vec!["a".to_string(), "b".to_string()]);
+        let e = PassError::with_source("could not order the cells", cause.clone());
+        let source = e.source().unwrap();
+        assert_eq!(source.downcast_ref::<CycleError>(), Some(&cause));
+        assert_eq!(
+            e,
+            PassError::with_source("could not order the cells", cause)
+        );
+        assert_ne!(e, PassError::new("could not order the cells"));
+    }
+
+    #[cfg(feature = "debug-errors")]
+    #[test]
+    fn test_stack() {
+        let e = parse("module \\a\n  wire $b\n  connect $b 3'10\nend\n").unwrap_err();
+        let (line, column, kind) = &e.stack()[0];
+        assert_eq!((*line, *column), (3, 14));
+        assert_eq!(kind, "Nom(Verify)");
+        let contexts: Vec<&str> = e.stack().iter().map(|(_, _, kind)| kind.as_str()).collect();
+        assert_eq!(contexts[1..], ["Context(\"connect\")"]);
+    }
+}
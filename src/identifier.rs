@@ -4,11 +4,13 @@
 
 use std::hash::Hasher;
 
-use crate::{Id, Span};
+use crate::{IResult, Id, ParseOptions, Span};
 use nom::{
     branch::alt,
-    bytes::complete::{tag, take_while1},
-    IResult,
+    bytes::complete::{tag, take_while, take_while1},
+    character::complete::satisfy,
+    combinator::{recognize, verify},
+    sequence::pair,
 };
 use nom_tracable::tracable_parser;
 
@@ -52,11 +54,79 @@ fn autogen_id(input: Span) -> IResult<Span, Id> {
     Ok((input, Id::Autogen(id.fragment().to_string())))
 }
 
+/// The words of RTLIL, never taken for a bare id, with whether they start a statement rather
+/// than an option
+const KEYWORDS: &[(&str, bool)] = &[
+    ("always", false),
+    ("assign", true),
+    ("attribute", true),
+    ("autoidx", true),
+    ("case", true),
+    ("cell", true),
+    ("connect", true),
+    ("edge", false),
+    ("end", true),
+    ("global", false),
+    ("high", false),
+    ("init", false),
+    ("inout", false),
+    ("input", false),
+    ("low", false),
+    ("memory", true),
+    ("memwr", true),
+    ("module", true),
+    ("negedge", false),
+    ("offset", false),
+    ("output", false),
+    ("parameter", true),
+    ("posedge", false),
+    ("process", true),
+    ("real", false),
+    ("signed", false),
+    ("size", false),
+    ("switch", true),
+    ("sync", true),
+    ("update", true),
+    ("upto", false),
+    ("width", false),
+    ("wire", true),
+];
+
+/// Whether `word` is one of RTLIL's
+pub(crate) fn is_keyword(word: &str) -> bool {
+    KEYWORDS.iter().any(|(keyword, _)| *keyword == word)
+}
+
+/// Whether `word` starts a statement of RTLIL
+pub(crate) fn starts_statement(word: &str) -> bool {
+    KEYWORDS.contains(&(word, true))
+}
+
+/// A public id written without its `\\`, accepted in lenient mode: `[a-zA-Z_][a-zA-Z0-9_$.]*`
+///
+/// The first character rules out constants, the restricted alphabet leaves the brackets of a
+/// range or a concatenation alone, and keywords are rejected.
+fn bare_id(input: Span) -> IResult<Span, Id> {
+    let (input, id) = verify(
+        recognize(pair(
+            satisfy(|c| c.is_ascii_alphabetic() || c == '_'),
+            take_while(|c: char| c.is_ascii_alphanumeric() || "_$.".contains(c)),
+        )),
+        |id: &Span| !is_keyword(id.fragment()),
+    )(input)?;
+    ParseOptions::report(id, "public id without its `\\`");
+    Ok((input, Id::Public(id.fragment().to_string())))
+}
+
 /// <id>            ::= <public-id> | <autogen-id>
 #[tracable_parser]
 #[inline]
 pub(crate) fn id(input: Span) -> IResult<Span, Id> {
-    alt((public_id, autogen_id))(input)
+    if ParseOptions::lenient() {
+        alt((public_id, autogen_id, bare_id))(input)
+    } else {
+        alt((public_id, autogen_id))(input)
+    }
 }
 
 #[cfg(test)]
@@ -120,4 +190,66 @@ mod tests {
         assert_eq!(a.inner(), b.inner());
         assert_eq!(a.erease(), b.erease());
     }
+
+    #[test]
+    fn test_bare_id() {
+        let span = LocatedSpan::new_extra("foo", Default::default());
+        assert!(id(span).is_err());
+        let lenient = ParseOptions {
+            lenient: true,
+            ..Default::default()
+        };
+        let vectors = [
+            ("foo", Some(("foo", ""))),
+            ("_a.b$1 [3:0]", Some(("_a.b$1", " [3:0]"))),
+            ("a}", Some(("a", "}"))),
+            ("wire", None),
+            ("1foo", None),
+            ("-1", None),
+        ];
+        for (input, expected) in vectors {
+            let span = LocatedSpan::new_extra(input, Default::default());
+            let ret = lenient.scope(|| id(span));
+            let ret = ret.ok().map(|(rem, id)| (id, *rem.fragment()));
+            let expected = expected.map(|(id, rem)| (Id::Public(id.into()), rem));
+            assert_eq!(ret, expected, "failed: {}", input);
+        }
+        // still an autogen id
+        let span = LocatedSpan::new_extra("$foo", Default::default());
+        assert_eq!(
+            lenient.scope(|| id(span)).unwrap().1,
+            Id::Autogen("foo".into())
+        );
+    }
+
+    #[test]
+    fn test_bare_id_design() {
+        let input = "module \\m\n  wire width 2 foo\n  connect foo [0] bar\nend\n";
+        assert!(crate::parse(input).is_err());
+        let design = crate::parse_with(
+            input,
+            &ParseOptions {
+                lenient: true,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        let module = &design.modules()["m"];
+        assert!(module.wire("foo").is_some());
+        assert_eq!(
+            module.connections()[0],
+            (
+                crate::SigSpec::range("foo", 0, None),
+                crate::SigSpec::wire("bar")
+            )
+        );
+    }
+
+    #[test]
+    fn test_keywords() {
+        assert!(is_keyword("width") && !starts_statement("width"));
+        assert!(is_keyword("wire") && starts_statement("wire"));
+        assert!(starts_statement("update"));
+        assert!(!is_keyword("foo") && !starts_statement("foo"));
+    }
 }
@@ -0,0 +1,105 @@
+//! JSON Lines output: one JSON object per line, for loading designs into other tools.
+
+use std::{collections::BTreeMap, io::Write};
+
+use serde::Serialize;
+
+use crate::*;
+
+/// A line of `Design::write_cells_jsonl`
+#[derive(Serialize)]
+struct CellLine<'a> {
+    module: &'a str,
+    name: &'a str,
+    #[serde(rename = "type")]
+    cell_type: &'a str,
+    params: BTreeMap<&'a str, &'a Constant>,
+    conns: BTreeMap<&'a str, &'a SigSpec>,
+}
+
+impl Design {
+    /// Write one JSON object per cell and per line to `w`, modules and cells sorted by name
+    ///
+    /// Each object holds the `module` and `name` of the cell, its `type`, its `params` and its
+    /// `conns`, names without sigil. Cells are written as they are visited, the design is never
+    /// held as one JSON value.
+    ///
+    /// ```
+    /// let src = "module \\m\n  wire \\a\n  cell $not $n\n    connect \\A \\a\n  end\nend\n";
+    /// let design = rtlicious::parse(src).unwrap();
+    /// let mut out = vec![];
+    /// design.write_cells_jsonl(&mut out).unwrap();
+    /// assert_eq!(
+    ///     String::from_utf8(out).unwrap(),
+    ///     "{\"module\":\"m\",\"name\":\"n\",\"type\":\"not\",\"params\":{},\
+    ///      \"conns\":{\"A\":{\"WireId\":\"a\"}}}\n"
+    /// );
+    /// ```
+    pub fn write_cells_jsonl<W: Write>(&self, mut w: W) -> std::io::Result<()> {
+        let modules: BTreeMap<&String, &Module> = self.modules.iter().collect();
+        for (module_name, module) in modules {
+            let cells: BTreeMap<&String, &Cell> = module.cells.iter().collect();
+            for (name, cell) in cells {
+                let line = CellLine {
+                    module: module_name,
+                    name,
+                    cell_type: &cell.cell_type,
+                    params: cell
+                        .parameters
+                        .iter()
+                        .map(|(name, value)| (name.as_str(), value))
+                        .collect(),
+                    conns: cell
+                        .connections
+                        .iter()
+                        .map(|(port, signal)| (port.as_str(), signal))
+                        .collect(),
+                };
+                serde_json::to_writer(&mut w, &line)?;
+                w.write_all(b"\n")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use indoc::indoc;
+
+    #[test]
+    fn test_write_cells_jsonl() {
+        let input = indoc! {r#"
+            module \a
+              wire \x
+              cell $not $n1
+                connect \A \x
+              end
+              cell $and $n2
+                parameter \A_WIDTH 1
+              end
+            end
+            module \b
+              cell \a $u
+              end
+            end
+        "#};
+        let design = parse(input).unwrap();
+        let mut out = vec![];
+        design.write_cells_jsonl(&mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        assert_eq!(out.lines().count(), design.cell_count());
+        assert!(out.ends_with('\n'));
+        let and = out.lines().nth(1).unwrap();
+        assert_eq!(
+            and,
+            r#"{"module":"a","name":"n2","type":"and","params":{"A_WIDTH":{"Integer":1}},"conns":{}}"#
+        );
+        assert!(out
+            .lines()
+            .last()
+            .unwrap()
+            .starts_with(r#"{"module":"b","name":"u","type":"a""#));
+    }
+}
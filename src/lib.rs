@@ -10,24 +10,42 @@
 //! let design = rtlicious::parse(src).unwrap();
 //! assert_eq!(design.modules().len(), 1);
 //! ```
+#[cfg(test)]
+mod arbitrary;
 mod attribute;
+mod borrowed;
+mod canonical;
 mod cell;
 mod characters;
 mod connect;
 mod constant;
 mod design;
+mod error;
 mod identifier;
+mod jsonl;
 mod memory;
 mod module;
+mod netlist;
+mod options;
+mod pass;
+mod ports;
+pub mod prelude;
 mod process;
+mod recover;
 mod sigspec;
+mod srcloc;
 mod string;
 mod switch;
 mod sync;
+mod validate;
 mod value;
 mod wire;
+mod writer;
 
-use std::collections::HashMap;
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
 
 use getset::Getters;
 use nom_locate::LocatedSpan;
@@ -53,6 +71,34 @@ pub struct Design {
     modules: HashMap<String, Module>,
 }
 
+/// A design read without copying its text, see `parse_borrowed`
+#[derive(Debug, Clone, PartialEq)]
+pub struct DesignRef<'a> {
+    /// The global autoindex value
+    autoidx: Option<i32>,
+    /// The modules in the design, in the order of the input
+    modules: Vec<ModuleRef<'a>>,
+}
+
+/// A module of a `DesignRef`: its names borrow the input, its body is parsed on demand
+#[derive(Debug, Clone, PartialEq)]
+pub struct ModuleRef<'a> {
+    /// The name of the module
+    name: &'a str,
+    /// The text of the module, from its first attribute to its `end`
+    source: &'a str,
+    /// The line `source` starts on, starting at 1
+    line: u32,
+    /// The names of the wires, in declaration order
+    wires: Vec<&'a str>,
+    /// The names of the memories, in declaration order
+    memories: Vec<&'a str>,
+    /// The names and types of the cells, in declaration order
+    cells: Vec<(&'a str, &'a str)>,
+    /// The names of the processes, in declaration order
+    processes: Vec<&'a str>,
+}
+
 /// Represents a module
 /// A module is a collection of wires, memories, cells, processes, and connections
 #[derive(Debug, Clone, PartialEq, Getters, Serialize)]
@@ -72,27 +118,72 @@ pub struct Module {
     processes: HashMap<String, Process>,
     /// The connections of the module
     connections: Vec<(SigSpec, SigSpec)>,
+    /// The lines of the module body starting with an unknown keyword, without their
+    /// indentation, only kept in lenient mode and not written back
+    unknown_stmts: Vec<String>,
+    /// Whether the module was parsed with `ParseOptions::partial`, from a dump that may leave
+    /// out the wires its signals refer to
+    partial: bool,
+}
+
+/// The owned contents of a module, see `Module::into_parts`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ModuleParts {
+    /// The attributes of the module
+    pub attributes: HashMap<String, Constant>,
+    /// The parameters of the module
+    pub parameters: HashMap<String, Option<Constant>>,
+    /// The wires of the module
+    pub wires: HashMap<Id, Wire>,
+    /// The memories of the module
+    pub memories: HashMap<String, Memory>,
+    /// The cells of the module
+    pub cells: HashMap<String, Cell>,
+    /// The processes of the module
+    pub processes: HashMap<String, Process>,
+    /// The connections of the module
+    pub connections: Vec<(SigSpec, SigSpec)>,
+    /// The lines of the module body starting with an unknown keyword
+    pub unknown_stmts: Vec<String>,
+    /// Whether the module is from a partial dump
+    pub partial: bool,
 }
 
 /// Represents a logic cell
 #[derive(Debug, Clone, PartialEq, Getters, Serialize)]
 #[getset(get = "pub")]
 pub struct Cell {
+    /// The attributes of the cell
+    attributes: HashMap<String, Constant>,
     /// The type of the cell, ie. add, sub, etc.
     cell_type: String,
     /// The parameters of the cell
     parameters: HashMap<String, Constant>,
+    /// The kind of the parameters declared `signed` or `real`, plain parameters are absent
+    parameter_kinds: HashMap<String, ParameterKind>,
     /// The connections of the cell
     connections: HashMap<String, SigSpec>,
 }
 
+/// How a cell parameter is declared, `parameter signed \\A 1` or `parameter real \\B "1.5"`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[non_exhaustive]
+pub enum ParameterKind {
+    /// a signed parameter
+    Signed,
+    /// a real parameter, its value is the constant written by Yosys, ie. a string
+    Real,
+}
+
 /// Represents a wire
 #[derive(Debug, Clone, PartialEq, Getters, Serialize)]
 #[getset(get = "pub")]
 pub struct Wire {
     /// defaults to 1
     width: usize,
-    /// defaults to 0
+    /// The index of the least significant bit in the HDL source, defaults to 0
+    ///
+    /// Ranges over the wire do not use it, see `Module::resolve_range`.
     offset: usize,
     /// if the wire is an input to the module
     input: bool,
@@ -104,10 +195,68 @@ pub struct Wire {
     upto: bool,
     /// if the wire is signed? TODO: what is this?
     signed: bool,
+    /// the port index of the wire, if it is an input, output or inout
+    port_id: Option<usize>,
     /// attributes of the wire
     attributes: HashMap<String, Constant>,
 }
 
+/// A top-level item of a module, see `Module::items`
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[non_exhaustive]
+pub enum ModuleItem<'a> {
+    /// A parameter with its default value
+    Param(&'a str, Option<&'a Constant>),
+    /// A wire
+    Wire(&'a Id, &'a Wire),
+    /// A memory
+    Memory(&'a str, &'a Memory),
+    /// A cell
+    Cell(&'a str, &'a Cell),
+    /// A process
+    Process(&'a str, &'a Process),
+    /// A connection of a destination to a source
+    Connection(&'a SigSpec, &'a SigSpec),
+}
+
+/// Something driving a wire bit, see `Module::drivers`
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize)]
+#[non_exhaustive]
+pub enum Driver {
+    /// the wire is an input or inout port of the module
+    Port,
+    /// the connection at this index of the module connections
+    Connection(usize),
+    /// an output port of a cell: the cell name and the port name
+    Cell(String, String),
+    /// a process, through its assignments and updates
+    Process(String),
+}
+
+/// The direction of a port
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+pub enum PortDir {
+    /// an input port
+    Input,
+    /// an output port
+    Output,
+    /// a bidirectional port
+    Inout,
+}
+
+/// The direction of each port of a cell type, see `cell_port_directions`
+pub type PortDirTable = [(&'static str, PortDir)];
+
+/// The interface of a module: its ports with their direction and width, sorted by port id
+///
+/// Displayed as `a:in[1] b:out[2]`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Getters, Serialize)]
+#[getset(get = "pub")]
+pub struct ModuleSignature {
+    /// The name, direction and width of each port
+    ports: Vec<(String, PortDir, usize)>,
+}
+
 /// Represents a memory cell
 #[derive(Debug, Clone, PartialEq, Getters, Serialize)]
 #[getset(get = "pub")]
@@ -136,8 +285,85 @@ pub struct Process {
     syncs: Vec<Sync>,
 }
 
+/// A transformation of a design, run by `Design::apply`
+pub trait Pass {
+    /// Transform `design` in place
+    fn run(&self, design: &mut Design) -> Result<(), PassError>;
+}
+
+/// A pass removing the wires no signal refers to, see `Module::remove_unused_wires`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RemoveUnusedWires;
+
+/// Implemented by the items that carry attributes.
+///
+/// Attribute names are stored without their leading `\`, the lookups accept them either way.
+pub trait HasAttributes {
+    /// The attributes of the item
+    fn attributes(&self) -> &HashMap<String, Constant>;
+
+    /// The attributes of the item, to edit them in place
+    fn attributes_mut(&mut self) -> &mut HashMap<String, Constant>;
+
+    /// Set an attribute, returning its previous value if it had one
+    fn set_attribute(&mut self, name: &str, value: Constant) -> Option<Constant> {
+        let name = name.strip_prefix('\\').unwrap_or(name);
+        self.attributes_mut().insert(name.to_string(), value)
+    }
+
+    /// Get an attribute by name
+    fn attribute(&self, name: &str) -> Option<&Constant> {
+        let name = name.strip_prefix('\\').unwrap_or(name);
+        self.attributes().get(name)
+    }
+
+    /// Get an attribute by name if it is an integer
+    fn attribute_int(&self, name: &str) -> Option<i32> {
+        match self.attribute(name)? {
+            Constant::Integer(i) => Some(*i),
+            _ => None,
+        }
+    }
+
+    /// Get an attribute by name if it is a string, ie. `\src`
+    fn attribute_str(&self, name: &str) -> Option<&str> {
+        match self.attribute(name)? {
+            Constant::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// Get an attribute by name as a flag, ie. `\keep`: `0` is false, any other number is true
+    ///
+    /// Integers and values made of `0` and `1` bits are numbers, other constants give `None`.
+    fn attribute_bool(&self, name: &str) -> Option<bool> {
+        match self.attribute(name)? {
+            Constant::String(_) | Constant::Real(_) => None,
+            constant => constant.as_i64().map(|i| i != 0),
+        }
+    }
+
+    /// The first source location of the `\src` attribute
+    fn src(&self) -> Option<SrcLoc> {
+        self.attribute("src").and_then(parse_src)
+    }
+}
+
+/// A source location, as found in `\src` attributes: `file:line.col-line.col`
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Getters, Serialize)]
+#[getset(get = "pub")]
+pub struct SrcLoc {
+    /// The source file
+    file: String,
+    /// The line and column the location starts at
+    start: (usize, usize),
+    /// The line and column the location ends at
+    end: (usize, usize),
+}
+
 /// Constant enum
-#[derive(Debug, Clone, PartialEq, Serialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize)]
+#[non_exhaustive]
 pub enum Constant {
     /// Value variant, contains a vector of characters, ie. vec!['x', 'z', '1', 'm']
     Value(Vec<char>),
@@ -145,23 +371,70 @@ pub enum Constant {
     Integer(i32),
     /// String variant, contains a String
     String(String),
+    /// Real variant, ie. `10.0`, kept as written so that it is written back unchanged
+    ///
+    /// See `Constant::as_f64` for its value.
+    Real(String),
+    /// A value written as a single bit repeated to its width, ie. `4'1`: the bit and the width
+    ///
+    /// Only kept with `ParseOptions::exact_values`, it is otherwise parsed as a `Value`.
+    Fill(char, usize),
+}
+
+/// The variant of a `Constant`, see `Constant::kind`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[non_exhaustive]
+pub enum ConstantKind {
+    /// `Constant::Value`
+    Value,
+    /// `Constant::Integer`
+    Integer,
+    /// `Constant::String`
+    String,
+    /// `Constant::Real`
+    Real,
+    /// `Constant::Fill`
+    Fill,
 }
 
 /// Represents a signal specification
-#[derive(Debug, Clone, PartialEq, Serialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize)]
+#[non_exhaustive]
 pub enum SigSpec {
     /// A constant value
     Constant(Constant),
     /// A wire id
+    ///
+    /// Brackets without a space before them are part of the id, as in the names Yosys gives
+    /// wires such as `$0\q[3:0]`, while `\q [3:0]` is a range of `\q`. See
+    /// `SigSpec::split_trailing_range` for inputs where they mean a range.
     WireId(String),
-    /// A range of bits from a wire
+    /// A range of bits from a wire: `[start:end]` or `[start]`
+    ///
+    /// The indices count the bits of the wire from its least significant one, starting at 0,
+    /// whatever the offset of the wire. They are kept and written as parsed.
     Range(Box<SigSpec>, usize, Option<usize>),
     /// A concatenation of signals
     Concat(Vec<SigSpec>),
 }
 
+/// The variant of a `SigSpec`, see `SigSpec::kind`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[non_exhaustive]
+pub enum SigSpecKind {
+    /// `SigSpec::Constant`
+    Constant,
+    /// `SigSpec::WireId`
+    WireId,
+    /// `SigSpec::Range`
+    Range,
+    /// `SigSpec::Concat`
+    Concat,
+}
+
 /// Represents a case body
 #[derive(Debug, Clone, PartialEq, Serialize)]
+#[non_exhaustive]
 pub enum CaseBody {
     /// another switch, nested
     Switch(Switch),
@@ -193,8 +466,52 @@ pub struct Switch {
     pub(crate) cases: Vec<Case>,
 }
 
+/// Counts of the items of a design, see `Design::statistics`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct DesignStats {
+    /// The number of modules
+    pub modules: usize,
+    /// The module with the `top` attribute, see `Design::top_module`
+    pub top: Option<String>,
+    /// The number of wires, across all modules
+    pub wires: usize,
+    /// The number of cells, across all modules
+    pub cells: usize,
+    /// The number of memories, across all modules
+    pub memories: usize,
+    /// The number of processes, across all modules
+    pub processes: usize,
+    /// The number of cells of each type, ie. `add` or the name of a module
+    pub cell_types: HashMap<String, usize>,
+}
+
+/// The flip-flop a process describes, see `Process::infer_register`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RegisterInfo {
+    /// The clock signal
+    pub clock: SigSpec,
+    /// The clock edge, `Posedge` or `Negedge`
+    pub edge: SignalSync,
+    /// The `(q, value)` updates of the `sync init`, empty without one
+    pub init: Vec<(SigSpec, SigSpec)>,
+    /// The `(q, d)` updates on the clock edge
+    pub updates: Vec<(SigSpec, SigSpec)>,
+}
+
+/// Size and nesting of the decision tree of a process, see `Process::complexity`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct ProcessMetrics {
+    /// The deepest nesting of switches, 0 without any switch
+    pub switch_depth: usize,
+    /// The number of cases, across all the switches
+    pub case_count: usize,
+    /// The number of assignments, in the process body and in the cases
+    pub assign_count: usize,
+}
+
 /// Represents a sync statement
 #[derive(Debug, Clone, PartialEq, Serialize)]
+#[non_exhaustive]
 pub enum SyncOn {
     /// Global sync
     Global,
@@ -208,6 +525,7 @@ pub enum SyncOn {
 
 /// Represents a
 #[derive(Debug, Clone, PartialEq, Serialize)]
+#[non_exhaustive]
 pub enum SignalSync {
     /// Low level sync
     Low,
@@ -228,11 +546,23 @@ pub struct Sync {
     /// The sync event
     sync_event: SyncOn,
     /// The updates to apply on the sync event
-    updates: Vec<(SigSpec, SigSpec)>,
+    updates: Vec<Update>,
     /// memwr statements
     memwrs: HashMap<String, Memwr>,
 }
 
+/// Represents an update statement of a sync
+#[derive(Debug, Clone, PartialEq, Getters, Serialize)]
+#[getset(get = "pub")]
+pub struct Update {
+    /// The attributes of the update
+    attributes: HashMap<String, Constant>,
+    /// The signal updated
+    dest: SigSpec,
+    /// The value it is updated with
+    src: SigSpec,
+}
+
 /// Represents a memwr statement
 #[derive(Debug, Clone, PartialEq, Getters, Serialize)]
 #[getset(get = "pub")]
@@ -249,19 +579,263 @@ pub struct Memwr {
     priority_mask: SigSpec,
 }
 
+/// Error returned when a design cannot be parsed, locating where the parser got stuck
+#[derive(Debug, Clone, PartialEq, Eq, Getters)]
+#[getset(get = "pub")]
+pub struct ParseError {
+    /// The line the failing element starts on, starting at 1
+    line: u32,
+    /// The column the failing element starts at, starting at 1
+    column: usize,
+    /// The text of the failing line
+    snippet: String,
+    /// The kind of the innermost failing parser, see `ParseError::nom_kind`
+    #[getset(skip)]
+    nom_kind: Option<nom::error::ErrorKind>,
+    /// Line, column and kind of every failing parser, from the innermost outwards
+    #[cfg(feature = "debug-errors")]
+    stack: Vec<(u32, usize, String)>,
+}
+
+/// Error returned by `Design::rename_module`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RenameError {
+    /// There is no module with the old name
+    NotFound(String),
+    /// A module with the new name already exists
+    AlreadyExists(String),
+}
+
+/// Error returned by `Design::verify_single_top`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TopError {
+    /// No module has the `top` attribute
+    NoTop,
+    /// Several modules have the `top` attribute, their names sorted
+    MultipleTops(Vec<String>),
+}
+
+/// Error returned by a `Pass` that could not transform a design
+#[derive(Debug, Clone, Getters)]
+#[getset(get = "pub")]
+pub struct PassError {
+    /// What went wrong
+    message: String,
+    /// The error that made the pass fail, see `PassError::with_source`
+    #[getset(skip)]
+    source: Option<Arc<dyn std::error::Error + Send + std::marker::Sync>>,
+}
+
+/// Error returned by `Module::topological_cells` when cells depend on each other in a loop
+#[derive(Debug, Clone, PartialEq, Eq, Getters)]
+#[getset(get = "pub")]
+pub struct CycleError {
+    /// The cells that could not be ordered, sorted: those on a loop and those depending on one
+    cells: Vec<String>,
+}
+
+/// A problem found by `Module::validate`
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ValidationError {
+    /// A signal refers to a wire, named without sigil, that the module does not declare
+    UnknownWire(String),
+    /// A range of a signal goes beyond the bits of its wire, named without sigil
+    RangeOutOfBounds {
+        /// The name of the wire
+        wire: String,
+        /// The highest index of the range
+        index: usize,
+    },
+    /// The two sides of the module connection at `index` have different widths
+    WidthMismatch {
+        /// The index of the connection in the module connections
+        index: usize,
+        /// The width of the destination
+        dest: usize,
+        /// The width of the source
+        src: usize,
+    },
+    /// The `\init` value of a wire, named without sigil, is not as wide as the wire
+    InitWidthMismatch {
+        /// The name of the wire
+        wire: String,
+        /// The width of the wire
+        width: usize,
+        /// The number of bits of the initial value
+        init: usize,
+    },
+}
+
+/// Indentation used for each nesting level when writing RTLIL
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Indent {
+    /// the given number of spaces per level
+    Spaces(usize),
+    /// one tab per level
+    Tab,
+}
+
+/// Options controlling how a design is written back to RTLIL
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WriteOptions {
+    /// The indentation of each nesting level, defaults to Yosys's 2 spaces
+    pub indent: Indent,
+    /// Sort the wires, memories, cells and processes of a module by their name with its sigil,
+    /// `$` before `\`, as Yosys does, rather than by their bare name. Defaults to `false`
+    pub yosys_order: bool,
+}
+
+/// Options controlling how strictly a design is parsed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseOptions {
+    /// Accept common deviations from the grammar found in hand-edited files, defaults to `false`
+    ///
+    /// * wire options after the wire id: `wire \a width 2`
+    /// * public ids without their `\`: `connect a b`
+    /// * cell connections without a port name, named `$0`, `$1`...: `connect \a`
+    /// * `connect` statements in case bodies, read as assignments
+    /// * module body lines starting with an unknown keyword, kept in `Module::unknown_stmts`
+    /// * form feeds and vertical tabs between tokens, read as spaces
+    pub lenient: bool,
+    /// The widest value accepted, in bits, defaults to `1 << 20`
+    ///
+    /// A wider value such as `999999999'0` fails to parse rather than allocating its bits, to
+    /// parse untrusted input safely.
+    pub max_value_width: usize,
+    /// Where to report the deviations from the grammar accepted in lenient mode, defaults to
+    /// `None` which logs them as warnings with `log`
+    pub diagnostics: Option<DiagnosticSink>,
+    /// Keep a value written as a single bit for a wider width, `4'1`, as a `Constant::Fill`, so
+    /// that it is written back as it was rather than as `4'1111`, defaults to `false`
+    pub exact_values: bool,
+    /// Read the input as a partial dump, such as `write_rtlil -selected` writes, whose signals
+    /// may refer to wires declared in what was not dumped, defaults to `false`
+    ///
+    /// The modules are marked partial: their unknown wires are reported as diagnostics at the
+    /// `module` statement, and `Module::validate` leaves them out.
+    pub partial: bool,
+}
+
+/// Receives the diagnostics of a parse, see `ParseOptions::diagnostics`
+///
+/// The options are cloned and shared by reference, so the callback is shared rather than boxed.
+///
+/// ```
+/// use std::sync::{Arc, Mutex};
+/// use rtlicious::{DiagnosticSink, ParseOptions};
+///
+/// let diagnostics = Arc::new(Mutex::new(vec![]));
+/// let sink = diagnostics.clone();
+/// let options = ParseOptions {
+///     lenient: true,
+///     diagnostics: Some(DiagnosticSink::new(move |d| sink.lock().unwrap().push(d))),
+///     ..Default::default()
+/// };
+/// rtlicious::parse_with("module \\m\n  wire a\nend\n", &options).unwrap();
+/// let diagnostics = diagnostics.lock().unwrap();
+/// assert_eq!(diagnostics[0].to_string(), "line 2, column 8: public id without its `\\`");
+/// ```
+#[derive(Clone)]
+pub struct DiagnosticSink(Arc<Mutex<dyn FnMut(Diagnostic) + Send>>);
+
+/// A deviation from the grammar accepted by a lenient parse
+#[derive(Debug, Clone, PartialEq, Eq, Getters)]
+#[getset(get = "pub")]
+pub struct Diagnostic {
+    /// The line of the deviation, from 1
+    line: u32,
+    /// The column of the deviation, from 1
+    column: usize,
+    /// What was accepted
+    message: String,
+}
+
 /// Input type must implement trait Tracable
 /// nom_locate::LocatedSpan<T, TracableInfo> implements it.
 type Span<'a> = LocatedSpan<&'a str, TracableInfo>;
 
+/// The error of the parsers, the whole stack of failing parsers with the `debug-errors` feature
+#[cfg(not(feature = "debug-errors"))]
+type NomError<I> = nom::error::Error<I>;
+#[cfg(feature = "debug-errors")]
+type NomError<I> = nom::error::VerboseError<I>;
+
+/// The result of the parsers
+type IResult<I, O, E = NomError<I>> = nom::IResult<I, O, E>;
+
+/// Parse a `\src` attribute value into its first source location.
+///
+/// Yosys joins several locations with `|`, see `parse_srcs` to get all of them.
+pub fn parse_src(attr: &Constant) -> Option<SrcLoc> {
+    parse_srcs(attr).into_iter().next()
+}
+
+/// Parse every `|`-separated source location of a `\src` attribute value
+pub fn parse_srcs(attr: &Constant) -> Vec<SrcLoc> {
+    match attr {
+        Constant::String(s) => s.split('|').filter_map(SrcLoc::from_str).collect(),
+        _ => vec![],
+    }
+}
+
+/// The direction of each port of a Yosys internal cell type, ie. `$dff` or `$add`
+///
+/// The sigil is optional. `None` for other cell types, such as instances of modules.
+///
+/// ```
+/// use rtlicious::{cell_port_directions, PortDir};
+///
+/// let dff = cell_port_directions("$dff").unwrap();
+/// assert_eq!(dff, [("CLK", PortDir::Input), ("D", PortDir::Input), ("Q", PortDir::Output)]);
+/// assert!(cell_port_directions("my_module").is_none());
+/// ```
+pub fn cell_port_directions(cell_type: &str) -> Option<&'static PortDirTable> {
+    ports::table(cell_type.strip_prefix('$').unwrap_or(cell_type))
+}
+
 /// Parse a RTLIL design from a type that implements `AsRef<str>`.
-pub fn parse(input: &str) -> Result<Design, Span> {
-    Design::new_from_str(input)
+pub fn parse(input: impl AsRef<str>) -> Result<Design, ParseError> {
+    Design::new_from_str(input.as_ref())
+}
+
+/// Read the structure of a RTLIL design without copying its names, see `DesignRef::scan`
+pub fn parse_borrowed(input: &str) -> Result<DesignRef<'_>, ParseError> {
+    DesignRef::scan(input)
+}
+
+/// Parse several RTLIL designs concatenated in one input, see `Design::new_multi_from_str`
+pub fn parse_multi(input: impl AsRef<str>) -> Result<Vec<Design>, ParseError> {
+    Design::new_multi_from_str(input.as_ref())
+}
+
+/// Parse what can be parsed of a RTLIL design, returning it with an error per skipped statement,
+/// see `Design::new_recovering_from_str`
+pub fn parse_recovering(input: impl AsRef<str>) -> (Design, Vec<ParseError>) {
+    Design::new_recovering_from_str(input.as_ref())
+}
+
+/// Parse a RTLIL design with the given options
+pub fn parse_with(input: impl AsRef<str>, options: &ParseOptions) -> Result<Design, ParseError> {
+    Design::new_from_str_with(input.as_ref(), options)
 }
 
 #[cfg(test)]
 mod tests {
+    use super::*;
+    use std::borrow::Cow;
+
     #[test]
     fn test_sanity() {
         assert_eq!(1 + 1, 2);
     }
+
+    #[test]
+    fn test_parse_as_ref() {
+        let input = String::from("module \\a\nend\n");
+        assert_eq!(parse(&input).unwrap().modules().len(), 1);
+        assert_eq!(parse(Cow::from(input.as_str())).unwrap().modules().len(), 1);
+        assert_eq!(parse(input).unwrap().modules().len(), 1);
+        assert!(parse(String::from("module")).is_err());
+    }
 }
@@ -10,11 +10,43 @@ use nom::{
     bytes::complete::tag,
     multi::many0,
     sequence::{preceded, terminated},
-    IResult,
 };
 use nom_tracable::tracable_parser;
 use std::collections::HashMap;
 
+impl Memory {
+    /// The total number of bits, `width * size`, `None` if it overflows a `usize`
+    pub fn bit_count(&self) -> Option<usize> {
+        self.width.checked_mul(self.size)
+    }
+
+    /// The number of bits needed to address every word, `ceil(log2(size))`
+    pub fn address_bits(&self) -> usize {
+        match self.size {
+            0 | 1 => 0,
+            size => (usize::BITS - (size - 1).leading_zeros()) as usize,
+        }
+    }
+
+    /// The number of read ports, from the `\rd_ports` attribute, 0 without it
+    pub fn read_ports(&self) -> usize {
+        self.port_count("rd_ports")
+    }
+
+    /// The number of write ports, from the `\wr_ports` attribute, 0 without it
+    pub fn write_ports(&self) -> usize {
+        self.port_count("wr_ports")
+    }
+
+    /// The attribute `name` as a count, 0 if it is missing, negative or not a number
+    fn port_count(&self, name: &str) -> usize {
+        self.attribute(name)
+            .and_then(Constant::as_i64)
+            .and_then(|count| usize::try_from(count).ok())
+            .unwrap_or(0)
+    }
+}
+
 #[tracable_parser]
 pub(crate) fn memory(input: Span) -> IResult<Span, (String, Memory)> {
     let (input, attributes) = many0(attribute::attr_stmt)(input)?;
@@ -102,6 +134,18 @@ mod tests {
         }
     }
     #[test]
+    fn test_port_counts() {
+        let input =
+            "attribute \\rd_ports 2\nattribute \\wr_ports -1\nmemory width 8 size 4 \\mem\n";
+        let span = Span::new_extra(input, Default::default());
+        let (_, (_, memory)) = memory(span).unwrap();
+        assert_eq!(memory.read_ports(), 2);
+        assert_eq!(memory.write_ports(), 0);
+        let span = Span::new_extra("memory width 8 size 4 \\mem\n", Default::default());
+        let (_, (_, memory)) = super::memory(span).unwrap();
+        assert_eq!(memory.read_ports(), 0);
+    }
+    #[test]
     fn test_memory_option() {
         let vectors = vec![
             ("width 32", MemoryOption::Width(32)),
@@ -133,4 +177,24 @@ mod tests {
             assert_eq!(ret.1, expected);
         }
     }
+
+    #[test]
+    fn test_memory_size() {
+        let memory = |width, size| Memory {
+            width,
+            size,
+            offset: 0,
+            attributes: HashMap::new(),
+        };
+        assert_eq!(memory(32, 1024).bit_count(), Some(32768));
+        assert_eq!(memory(32, 1024).address_bits(), 10);
+        assert_eq!(memory(8, 1000).address_bits(), 10);
+        assert_eq!(memory(8, 1025).address_bits(), 11);
+        assert_eq!(memory(8, 2).address_bits(), 1);
+        assert_eq!(memory(8, 1).address_bits(), 0);
+        assert_eq!(memory(8, 0).address_bits(), 0);
+        assert_eq!(memory(8, 0).bit_count(), Some(0));
+        assert_eq!(memory(usize::MAX, 2).bit_count(), None);
+        assert_eq!(memory(1, usize::MAX).address_bits(), usize::BITS as usize);
+    }
 }
@@ -16,20 +16,323 @@
 use crate::*;
 use nom::{
     branch::alt,
-    bytes::complete::tag,
-    combinator::{map, opt},
+    bytes::complete::{tag, take_till1},
+    combinator::{map, opt, verify},
+    error::context,
     multi::many0,
     sequence::preceded,
-    IResult,
 };
 use nom_tracable::tracable_parser;
-use std::collections::HashMap;
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+};
+
+impl Module {
+    /// Get a wire by name, whether it is public or auto-generated
+    pub fn wire(&self, name: &str) -> Option<&Wire> {
+        self.wires
+            .get(&Id::Public(name.to_string()))
+            .or_else(|| self.wires.get(&Id::Autogen(name.to_string())))
+    }
+
+    /// Get a wire by name to edit it, whether it is public or auto-generated
+    pub fn wire_mut(&mut self, name: &str) -> Option<&mut Wire> {
+        let public = Id::Public(name.to_string());
+        let id = if self.wires.contains_key(&public) {
+            public
+        } else {
+            Id::Autogen(name.to_string())
+        };
+        self.wires.get_mut(&id)
+    }
+
+    /// Get a cell by name to edit it
+    pub fn cell_mut(&mut self, name: &str) -> Option<&mut Cell> {
+        self.cells.get_mut(name)
+    }
+
+    /// Whether the module is a blackbox, with a true `\blackbox` attribute: only its interface is
+    /// known, its body if any is not analyzed
+    pub fn is_blackbox(&self) -> bool {
+        self.attribute_bool("blackbox") == Some(true)
+    }
+
+    /// The port wires of the module, sorted by port id
+    pub fn ports(&self) -> Vec<(&str, &Wire)> {
+        self.ports_where(|wire| wire.input || wire.output || wire.inout)
+    }
+
+    /// The input ports of the module, sorted by port id
+    pub fn inputs(&self) -> Vec<(&str, &Wire)> {
+        self.ports_where(|wire| wire.input && !wire.inout)
+    }
+
+    /// The output ports of the module, sorted by port id
+    pub fn outputs(&self) -> Vec<(&str, &Wire)> {
+        self.ports_where(|wire| wire.output && !wire.inout)
+    }
+
+    /// The inout ports of the module, sorted by port id
+    pub fn inouts(&self) -> Vec<(&str, &Wire)> {
+        self.ports_where(|wire| wire.inout)
+    }
+
+    /// The interface of the module, to compare it with another one
+    pub fn signature(&self) -> ModuleSignature {
+        ModuleSignature {
+            ports: self
+                .ports()
+                .into_iter()
+                .filter_map(|(name, wire)| Some((name.to_string(), wire.direction()?, wire.width)))
+                .collect(),
+        }
+    }
+
+    /// Every top-level item of the module, in the order they are written back: parameters,
+    /// wires, memories, cells and processes sorted by name, then connections in source order
+    pub fn items(&self) -> impl Iterator<Item = ModuleItem<'_>> {
+        fn sorted<V>(map: &HashMap<String, V>) -> Vec<(&str, &V)> {
+            let mut items: Vec<_> = map.iter().map(|(k, v)| (k.as_str(), v)).collect();
+            items.sort_by_key(|item| item.0);
+            items
+        }
+        let mut wires: Vec<_> = self.wires.iter().collect();
+        wires.sort_by_key(|(id, _)| (id.inner(), matches!(id, Id::Autogen(_))));
+        let parameters = sorted(&self.parameters)
+            .into_iter()
+            .map(|(name, value)| ModuleItem::Param(name, value.as_ref()));
+        let wires = wires
+            .into_iter()
+            .map(|(id, wire)| ModuleItem::Wire(id, wire));
+        let memories = sorted(&self.memories)
+            .into_iter()
+            .map(|(name, memory)| ModuleItem::Memory(name, memory));
+        let cells = sorted(&self.cells)
+            .into_iter()
+            .map(|(name, cell)| ModuleItem::Cell(name, cell));
+        let processes = sorted(&self.processes)
+            .into_iter()
+            .map(|(name, process)| ModuleItem::Process(name, process));
+        let connections = self
+            .connections
+            .iter()
+            .map(|(dest, src)| ModuleItem::Connection(dest, src));
+        parameters
+            .chain(wires)
+            .chain(memories)
+            .chain(cells)
+            .chain(processes)
+            .chain(connections)
+    }
+
+    fn ports_where(&self, f: impl Fn(&Wire) -> bool) -> Vec<(&str, &Wire)> {
+        let mut ports: Vec<(&str, &Wire)> = self
+            .wires
+            .iter()
+            .filter(|(_, wire)| f(wire))
+            .map(|(id, wire)| (id.inner().as_str(), wire))
+            .collect();
+        ports.sort_by_key(|(id, wire)| (wire.port_id.is_none(), wire.port_id, *id));
+        ports
+    }
+
+    /// The width in bits of a sigspec, `None` if it references a wire not declared in the module
+    /// or a range beyond the bits of its signal.
+    ///
+    /// Integers are 32 bits wide and strings 8 bits per character, an empty concatenation `{ }`
+    /// is 0 bits wide. Reals have no width.
+    pub fn sigspec_width(&self, sigspec: &SigSpec) -> Option<usize> {
+        // a stack rather than recursion, for deeply nested concatenations
+        let mut stack = vec![sigspec];
+        let mut width = 0;
+        while let Some(sigspec) = stack.pop() {
+            width += match sigspec {
+                SigSpec::Constant(Constant::Value(bits)) => bits.len(),
+                SigSpec::Constant(Constant::Fill(_, width)) => *width,
+                SigSpec::Constant(Constant::Integer(_)) => 32,
+                SigSpec::Constant(Constant::String(s)) => 8 * s.len(),
+                SigSpec::Constant(Constant::Real(_)) => return None,
+                SigSpec::WireId(id) => self.wire(id)?.width,
+                SigSpec::Range(inner, start, end) => {
+                    // the indices count from the least significant bit, whatever the offset
+                    let end = end.unwrap_or(*start);
+                    if *start.max(&end) >= self.sigspec_width(inner)? {
+                        return None;
+                    }
+                    start.abs_diff(end) + 1
+                }
+                SigSpec::Concat(parts) => {
+                    stack.extend(parts);
+                    0
+                }
+            };
+        }
+        Some(width)
+    }
+
+    /// The module connections as `(dest, src, dest width, src width)`, widths as given by
+    /// `sigspec_width`
+    pub fn connection_pairs(&self) -> Vec<(SigSpec, SigSpec, Option<usize>, Option<usize>)> {
+        self.connections
+            .iter()
+            .map(|(dest, src)| {
+                let widths = (self.sigspec_width(dest), self.sigspec_width(src));
+                (dest.clone(), src.clone(), widths.0, widths.1)
+            })
+            .collect()
+    }
+
+    /// The width of every wire, by name without sigil, for repeated lookups
+    pub fn net_width_map(&self) -> HashMap<String, usize> {
+        self.wires
+            .iter()
+            .map(|(id, wire)| (id.inner().clone(), wire.width))
+            .collect()
+    }
+
+    /// Iterate over the cells of the module whose `cell_type` is `ty`.
+    ///
+    /// Cell types are stored without their `$`/`\` prefix, a leading one in `ty` is ignored.
+    /// ```
+    /// let src = r#"module \comb_not1
+    ///   wire input 1 \a
+    ///   wire output 2 \b
+    ///   cell $logic_not $logic_not$vectors/comb_not1.v:7$2
+    ///     connect \A \a
+    ///     connect \Y \b
+    ///   end
+    /// end
+    /// "#;
+    /// let design = rtlicious::parse(src).unwrap();
+    /// let module = &design.modules()["comb_not1"];
+    /// assert_eq!(module.cells_of_type("$logic_not").count(), 1);
+    /// assert_eq!(module.cells_of_type("logic_not").count(), 1);
+    /// assert_eq!(module.cells_of_type("$dff").count(), 0);
+    /// ```
+    pub fn cells_of_type<'a>(&'a self, ty: &'a str) -> impl Iterator<Item = (&'a str, &'a Cell)> {
+        let ty = ty.strip_prefix(['$', '\\']).unwrap_or(ty);
+        self.cells
+            .iter()
+            .filter(move |(_, cell)| cell.cell_type == ty)
+            .map(|(id, cell)| (id.as_str(), cell))
+    }
+
+    /// Add the cell `name`, returning the cell it replaces if there was one
+    pub fn insert_cell(&mut self, name: impl Into<String>, cell: Cell) -> Option<Cell> {
+        self.cells.insert(name.into(), cell)
+    }
+
+    /// Replace the existing cell `name`, returning the old cell
+    ///
+    /// Nothing is inserted and `None` is returned if there is no such cell.
+    pub fn replace_cell(&mut self, name: &str, cell: Cell) -> Option<Cell> {
+        self.cells
+            .get_mut(name)
+            .map(|old| std::mem::replace(old, cell))
+    }
+
+    /// Remove the cell `name`, leaving the wires it connects in place
+    pub fn remove_cell(&mut self, name: &str) -> Option<Cell> {
+        self.cells.remove(name)
+    }
+
+    /// The source files named by the `\src` attributes of the module and of its wires,
+    /// memories, cells and processes, including their switches and cases
+    pub fn source_files(&self) -> HashSet<String> {
+        let mut items: Vec<&dyn HasAttributes> = vec![self];
+        items.extend(self.wires.values().map(|wire| wire as &dyn HasAttributes));
+        items.extend(
+            self.memories
+                .values()
+                .map(|memory| memory as &dyn HasAttributes),
+        );
+        items.extend(self.cells.values().map(|cell| cell as &dyn HasAttributes));
+        for process in self.processes.values() {
+            items.push(process);
+            let mut switches: Vec<&Switch> = process.switches.iter().collect();
+            while let Some(switch) = switches.pop() {
+                items.push(switch);
+                for case in &switch.cases {
+                    items.push(case);
+                    for body in &case.case_bodies {
+                        if let CaseBody::Switch(switch) = body {
+                            switches.push(switch);
+                        }
+                    }
+                }
+            }
+        }
+        items
+            .into_iter()
+            .filter_map(|item| item.attribute("src"))
+            .flat_map(parse_srcs)
+            .map(|src| src.file().clone())
+            .collect()
+    }
+
+    /// Take the module apart into its owned contents, `Module::from` puts it back together
+    pub fn into_parts(self) -> ModuleParts {
+        ModuleParts {
+            attributes: self.attributes,
+            parameters: self.parameters,
+            wires: self.wires,
+            memories: self.memories,
+            cells: self.cells,
+            processes: self.processes,
+            connections: self.connections,
+            unknown_stmts: self.unknown_stmts,
+            partial: self.partial,
+        }
+    }
+}
+
+impl From<ModuleParts> for Module {
+    fn from(parts: ModuleParts) -> Self {
+        Module {
+            attributes: parts.attributes,
+            parameters: parts.parameters,
+            wires: parts.wires,
+            memories: parts.memories,
+            cells: parts.cells,
+            processes: parts.processes,
+            connections: parts.connections,
+            unknown_stmts: parts.unknown_stmts,
+            partial: parts.partial,
+        }
+    }
+}
+
+impl<'a> IntoIterator for &'a Module {
+    type Item = ModuleItem<'a>;
+    type IntoIter = Box<dyn Iterator<Item = ModuleItem<'a>> + 'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        Box::new(self.items())
+    }
+}
+
+impl fmt::Display for ModuleSignature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, (name, direction, width)) in self.ports.iter().enumerate() {
+            let direction = match direction {
+                PortDir::Input => "in",
+                PortDir::Output => "out",
+                PortDir::Inout => "inout",
+            };
+            let sep = if i == 0 { "" } else { " " };
+            write!(f, "{}{}:{}[{}]", sep, name, direction, width)?;
+        }
+        Ok(())
+    }
+}
 
 #[tracable_parser]
 pub(crate) fn module(input: Span) -> IResult<Span, (String, Module)> {
     let (input, attributes) = many0(attribute::attr_stmt)(input)?;
     let attributes: HashMap<String, Constant> = attributes.into_iter().collect();
-    let (input, id) = module_stmt(input)?;
+    let start = input;
+    let (input, id) = context("module", module_stmt)(input)?;
 
     let mut parameters: HashMap<String, Option<Constant>> = HashMap::new();
     let mut wires = HashMap::new();
@@ -37,48 +340,73 @@ pub(crate) fn module(input: Span) -> IResult<Span, (String, Module)> {
     let mut processes = HashMap::new();
     let mut cells: HashMap<String, Cell> = HashMap::new();
     let mut connections: Vec<(SigSpec, SigSpec)> = Vec::new();
+    let mut unknown_stmts = Vec::new();
 
     // can be parameter, wire, memory, cell, process
+    // the contexts only show in the stack of errors kept by the `debug-errors` feature
     let (input, _) = many0(|input| {
         alt((
-            map(param_stmt, |(id, constant)| {
+            map(context("parameter", param_stmt), |(id, constant)| {
                 parameters.insert(id, constant);
             }),
-            map(crate::wire::wire, |wire| {
+            map(context("wire", crate::wire::wire), |wire| {
                 wires.insert(wire.0, wire.1);
             }),
-            map(crate::memory::memory, |mem| {
+            map(context("memory", crate::memory::memory), |mem| {
                 memories.insert(mem.0, mem.1);
             }),
-            map(crate::cell::cell, |found_cell| {
+            map(context("cell", crate::cell::cell), |found_cell| {
                 cells.insert(found_cell.0, found_cell.1);
             }),
-            map(crate::process::process, |process| {
+            map(context("process", crate::process::process), |process| {
                 processes.insert(process.0, process.1);
             }),
-            map(connect::conn_stmt, |(dst, src)| {
+            map(context("connect", connect::conn_stmt), |(dst, src)| {
                 connections.push((dst, src));
             }),
+            map(unknown_stmt, |line| unknown_stmts.push(line)),
         ))(input)
     })(input)?;
 
     let (input, _) = module_end_stmt(input)?;
 
-    Ok((
-        input,
-        (
-            id.erease(),
-            Module {
-                attributes,
-                parameters,
-                wires,
-                memories,
-                cells,
-                processes,
-                connections,
-            },
-        ),
-    ))
+    let module = Module {
+        attributes,
+        parameters,
+        wires,
+        memories,
+        cells,
+        processes,
+        connections,
+        unknown_stmts,
+        partial: ParseOptions::partial(),
+    };
+    if module.partial && !module.is_blackbox() {
+        for name in module.unknown_wires() {
+            ParseOptions::report(
+                start,
+                format!("no wire named {} in the partial module", name),
+            );
+        }
+    }
+    Ok((input, (id.erease(), module)))
+}
+
+/// The statements ended by an `end` line
+pub(crate) const BLOCKS: [&str; 4] = ["module", "cell", "process", "switch"];
+
+/// A whole line starting with a word that starts no statement of RTLIL
+///
+/// Only accepted in lenient mode. A known keyword on a line that fails to parse is still an
+/// error, so that a broken cell or process is not skipped line by line.
+fn unknown_stmt(input: Span) -> IResult<Span, String> {
+    let (input, line) = verify(take_till1(|c| c == '\n' || c == '\r'), |line: &Span| {
+        let keyword = line.split([' ', '\t']).next().unwrap_or_default();
+        ParseOptions::lenient() && !identifier::starts_statement(keyword)
+    })(input)?;
+    let (input, _) = characters::eol(input)?;
+    ParseOptions::report(line, "unknown statement kept in the module");
+    Ok((input, line.fragment().trim_end().to_string()))
 }
 
 /// `<module-stmt>       ::= module <id> <eol>`
@@ -91,10 +419,12 @@ pub(crate) fn module_stmt(input: Span) -> IResult<Span, Id> {
 }
 
 /// `<module-end-stmt>   ::= end <eol>`
+///
+/// The last module of a file may end without a newline.
 pub(crate) fn module_end_stmt(input: Span) -> IResult<Span, &str> {
     // eat whitespace if any
     let (input, _) = tag("end")(input)?;
-    let (input, _) = characters::eol(input)?;
+    let (input, _) = characters::eol_or_eof(input)?;
     Ok((input, ""))
 }
 
@@ -113,6 +443,31 @@ mod tests {
     use indoc::indoc;
     use pretty_assertions::assert_eq;
 
+    const COMB_NOT1: &str = indoc! {r#"
+        attribute \top 1
+        attribute \src "vectors/comb_not1.v:2.1-9.10"
+        module \comb_not1
+            attribute \src "vectors/comb_not1.v:6.5-8.8"
+            wire $0\b[0:0]
+            attribute \src "vectors/comb_not1.v:7.13-7.15"
+            wire $logic_not$vectors/comb_not1.v:7$2_Y
+            attribute \src "vectors/comb_not1.v:3.11-3.12"
+            wire input 1 \a
+            attribute \src "vectors/comb_not1.v:4.12-4.13"
+            wire output 2 \b
+            attribute \src "vectors/comb_not1.v:7.13-7.15"
+            cell $logic_not $logic_not$vectors/comb_not1.v:7$2
+                parameter \A_SIGNED 0
+                parameter \A_WIDTH 1
+                parameter \Y_WIDTH 1
+                connect \A \a
+                connect \Y $logic_not$vectors/comb_not1.v:7$2_Y
+            end
+            connect $0\b[0:0] $logic_not$vectors/comb_not1.v:7$2_Y
+            connect \b $logic_not$vectors/comb_not1.v:7$2_Y
+        end
+    "#};
+
     #[test]
     fn test_module() {
         let raw = indoc! {r#"
@@ -150,6 +505,321 @@ mod tests {
         assert_eq!(module.processes.len(), 0);
         assert_eq!(module.connections.len(), 2);
     }
+
+    #[test]
+    fn test_net_width_map() {
+        let span = Span::new_extra(COMB_NOT1, Default::default());
+        let (_, (_, module)) = module(span).unwrap();
+        let widths = module.net_width_map();
+        assert_eq!(widths.len(), 4);
+        // \b is the second port, it is 1 bit wide
+        assert_eq!(widths["b"], 1);
+        assert_eq!(widths["0\\b[0:0]"], 1);
+        for (name, width) in &widths {
+            assert_eq!(module.sigspec_width(&SigSpec::wire(name)), Some(*width));
+        }
+    }
+
+    #[test]
+    fn test_module_items() {
+        let span = Span::new_extra(COMB_NOT1, Default::default());
+        let (_, (_, module)) = module(span).unwrap();
+        let mut counts = [0; 6];
+        for item in &module {
+            let kind = match item {
+                ModuleItem::Param(..) => 0,
+                ModuleItem::Wire(..) => 1,
+                ModuleItem::Memory(..) => 2,
+                ModuleItem::Cell(..) => 3,
+                ModuleItem::Process(..) => 4,
+                ModuleItem::Connection(..) => 5,
+            };
+            counts[kind] += 1;
+        }
+        assert_eq!(counts, [0, 4, 0, 1, 0, 2]);
+        let wires: Vec<_> = module
+            .items()
+            .filter_map(|item| match item {
+                ModuleItem::Wire(id, _) => Some(id.to_string()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            wires,
+            [
+                "$0\\b[0:0]",
+                "\\a",
+                "\\b",
+                "$logic_not$vectors/comb_not1.v:7$2_Y"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_sigspec_width_large_concat() {
+        let (_, (_, module)) = module(Span::new_extra(
+            "module \\m\n  wire width 4096 \\a\nend\n",
+            Default::default(),
+        ))
+        .unwrap();
+        let concat = SigSpec::concat(
+            (0..4096)
+                .rev()
+                .map(|i| SigSpec::concat([SigSpec::range("a", i, None), SigSpec::value("10")])),
+        );
+        assert_eq!(module.sigspec_width(&concat), Some(3 * 4096));
+        let with_real = SigSpec::concat([concat, SigSpec::constant(Constant::Real("1.0".into()))]);
+        assert_eq!(module.sigspec_width(&with_real), None);
+    }
+
+    #[test]
+    fn test_source_files() {
+        let raw = indoc! {r#"
+        attribute \src "top.v:1.1-20.10"
+        module \top
+            attribute \src "top.v:2.12-2.13"
+            wire \a
+            attribute \src "alu.v:3.5-3.20|top.v:4.5-4.20"
+            cell $add $add$1
+            end
+            process $p
+                attribute \src "alu.v:9.3-9.9"
+                switch \a
+                    attribute \src "inc/mux.vh:1.1-1.2"
+                    case 1'1
+                end
+            end
+        end
+        "#};
+        let (_, (_, module)) = module(Span::new_extra(raw, Default::default())).unwrap();
+        assert_eq!(
+            module.source_files(),
+            HashSet::from(["top.v", "alu.v", "inc/mux.vh"].map(String::from))
+        );
+        let (_, (_, module)) =
+            super::module(Span::new_extra("module \\a\nend\n", Default::default())).unwrap();
+        assert!(module.source_files().is_empty());
+    }
+
+    #[test]
+    fn test_connection_pairs() {
+        let span = Span::new_extra(COMB_NOT1, Default::default());
+        let (_, (_, mut module)) = module(span).unwrap();
+        let y = SigSpec::wire("logic_not$vectors/comb_not1.v:7$2_Y");
+        assert_eq!(
+            module.connection_pairs(),
+            vec![
+                (SigSpec::wire("0\\b[0:0]"), y.clone(), Some(1), Some(1)),
+                (SigSpec::wire("b"), y.clone(), Some(1), Some(1)),
+            ]
+        );
+        module
+            .connections
+            .push((SigSpec::wire("c"), SigSpec::value("10")));
+        assert_eq!(
+            module.connection_pairs()[2],
+            (SigSpec::wire("c"), SigSpec::value("10"), None, Some(2))
+        );
+    }
+
+    #[test]
+    fn test_edit_cells() {
+        let raw = indoc! {r#"
+        module \comb_not1
+            wire $logic_not$vectors/comb_not1.v:7$2_Y
+            wire input 1 \a
+            wire output 2 \b
+            cell $logic_not $logic_not$vectors/comb_not1.v:7$2
+                parameter \A_SIGNED 0
+                connect \A \a
+                connect \Y $logic_not$vectors/comb_not1.v:7$2_Y
+            end
+            connect \b $logic_not$vectors/comb_not1.v:7$2_Y
+        end
+        "#};
+        let input = Span::new_extra(raw, Default::default());
+        let (_input, (_id, mut module)) = module(input).unwrap();
+        let name = "logic_not$vectors/comb_not1.v:7$2";
+        let cell = module.remove_cell(name).unwrap();
+        assert!(module.cells.is_empty());
+        assert_eq!(module.wires.len(), 3);
+        assert_eq!(module.connections.len(), 1);
+        assert_eq!(module.remove_cell(name), None);
+
+        assert_eq!(module.replace_cell(name, cell.clone()), None);
+        assert!(module.cells.is_empty());
+        assert_eq!(module.insert_cell(name, cell.clone()), None);
+        let mut not = cell.clone();
+        not.cell_type = "not".to_string();
+        assert_eq!(module.replace_cell(name, not), Some(cell));
+        assert_eq!(module.cells_of_type("$not").count(), 1);
+    }
+    #[test]
+    fn test_into_parts() {
+        let raw = indoc! {r#"
+        attribute \keep 1
+        module \a
+            wire input 1 \i
+            wire output 2 \o
+            connect \o \i
+        end
+        "#};
+        let (_, (_, module)) = module(Span::new_extra(raw, Default::default())).unwrap();
+        let mut parts = module.clone().into_parts();
+        assert_eq!(parts.wires.len(), 2);
+        assert_eq!(
+            parts.connections,
+            vec![(SigSpec::wire("o"), SigSpec::wire("i"))]
+        );
+        assert_eq!(Module::from(parts.clone()), module);
+
+        parts.connections.clear();
+        parts.wires.remove(&Id::Public("o".to_string()));
+        let module = Module::from(parts);
+        assert!(module.connections.is_empty());
+        assert!(module.wire("o").is_none());
+        assert_eq!(module.attributes.len(), 1);
+    }
+
+    #[test]
+    fn test_unknown_stmts() {
+        let raw = indoc! {r#"
+        module \a
+            wire \w
+            foobar \x 1
+            connect \w 1'0
+        end
+        "#};
+        let strict = module(Span::new_extra(raw, Default::default()));
+        assert!(strict.is_err());
+
+        let lenient = ParseOptions {
+            lenient: true,
+            ..Default::default()
+        };
+        let (rest, (_, module)) = lenient
+            .scope(|| module(Span::new_extra(raw, Default::default())))
+            .unwrap();
+        assert!(rest.is_empty());
+        assert_eq!(module.unknown_stmts, ["foobar \\x 1"]);
+        assert!(module.wire("w").is_some());
+        assert_eq!(module.connections.len(), 1);
+
+        // a known keyword that fails to parse is not skipped
+        let broken = raw.replace("foobar", "wire foo");
+        let broken = lenient.scope(|| super::module(Span::new_extra(&broken, Default::default())));
+        assert!(broken.is_err());
+    }
+
+    #[test]
+    fn test_ports() {
+        let raw = indoc! {r#"
+        module \a
+            wire output 2 \y
+            wire \internal
+            wire inout 3 \io
+            wire input 4 inout 4 \io2
+            wire input 1 \x
+        end
+        "#};
+        let input = Span::new_extra(raw, Default::default());
+        let (_input, (_id, module)) = module(input).unwrap();
+        fn names<'a>(ports: Vec<(&'a str, &Wire)>) -> Vec<&'a str> {
+            ports.into_iter().map(|p| p.0).collect()
+        }
+        assert_eq!(names(module.inputs()), vec!["x"]);
+        assert_eq!(names(module.outputs()), vec!["y"]);
+        assert_eq!(names(module.inouts()), vec!["io", "io2"]);
+        assert_eq!(names(module.ports()), vec!["x", "y", "io", "io2"]);
+    }
+
+    #[test]
+    fn test_signature() {
+        let stub = indoc! {r#"
+        module \stub
+            wire width 2 output 2 \b
+            wire input 1 \a
+        end
+        "#};
+        let implementation = indoc! {r#"
+        module \implementation
+            wire input 1 \a
+            wire $internal
+            cell $not $n
+                connect \A \a
+                connect \Y $internal
+            end
+            wire width 2 output 2 \b
+        end
+        "#};
+        let (_, (_, stub)) = module(Span::new_extra(stub, Default::default())).unwrap();
+        let (_, (_, implementation)) =
+            module(Span::new_extra(implementation, Default::default())).unwrap();
+        assert_eq!(stub.signature(), implementation.signature());
+        assert_eq!(stub.signature().to_string(), "a:in[1] b:out[2]");
+
+        let other = "module \\other\n  wire width 2 output 1 \\b\n  wire input 2 \\a\nend\n";
+        let (_, (_, other)) = module(Span::new_extra(other, Default::default())).unwrap();
+        assert_ne!(stub.signature(), other.signature());
+        assert_eq!(other.signature().to_string(), "b:out[2] a:in[1]");
+    }
+
+    #[test]
+    fn test_sigspec_width() {
+        let raw = indoc! {r#"
+        module \a
+            wire width 4 \a
+            wire $b
+            connect { } { }
+        end
+        "#};
+        let input = Span::new_extra(raw, Default::default());
+        let (_input, (_id, module)) = module(input).unwrap();
+        let (dest, src) = &module.connections[0];
+        assert_eq!(dest, &SigSpec::Concat(vec![]));
+        assert_eq!(module.sigspec_width(dest), Some(0));
+        assert_eq!(module.sigspec_width(src), Some(0));
+        let vectors = [
+            ("\\a", Some(4)),
+            ("$b", Some(1)),
+            ("\\c", None),
+            ("\\a [3:1]", Some(3)),
+            ("\\a [2]", Some(1)),
+            ("{ \\a $b 2'01 }", Some(7)),
+            ("{ \\a \\c }", None),
+            ("5", Some(32)),
+            ("\"ab\"", Some(16)),
+        ];
+        for (input, expected) in vectors {
+            let span = Span::new_extra(input, Default::default());
+            let sigspec = sigspec::sigspec(span).unwrap().1;
+            assert_eq!(
+                module.sigspec_width(&sigspec),
+                expected,
+                "failed: {}",
+                input
+            );
+        }
+    }
+
+    #[test]
+    fn test_sigspec_width_ranges() {
+        let design = crate::parse("module \\a\n  wire width 4 offset 8 \\a\nend\n").unwrap();
+        let module = &design.modules()["a"];
+        let vectors = [
+            ("\\a [3:0]", Some(4)),
+            ("\\a [0:3]", Some(4)),
+            ("\\a [4]", None),
+            ("\\a [8:8]", None),
+            ("\\nope [3:0]", None),
+            ("\\nope [0]", None),
+        ];
+        for (input, expected) in vectors {
+            let sigspec = SigSpec::try_from(input).unwrap();
+            assert_eq!(module.sigspec_width(&sigspec), expected, "{}", input);
+        }
+    }
+
     #[test]
     fn test_module_stmt() {
         let vectors = vec![
@@ -173,6 +843,23 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_module_end_with_comments() {
+        let raw = indoc! {r#"
+        module \a
+            wire $b
+            cell $not $c
+                connect \A $b
+            end # cell $c
+        end # module \a
+        "#};
+        let input = Span::new_extra(raw, Default::default());
+        let (rem, (id, module)) = module(input).unwrap();
+        assert!(rem.fragment().is_empty());
+        assert_eq!(id, "a");
+        assert_eq!(module.cells.len(), 1);
+    }
+
     #[test]
     fn test_param_stmt() {
         let vectors = vec![
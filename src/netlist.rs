@@ -0,0 +1,914 @@
+//! Connectivity analyses of a module: what drives each wire bit.
+//!
+//! Bits are addressed by wire name, without sigil, and index within the wire, as written in
+//! ranges.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+
+use crate::*;
+
+/// Whether `port` of `cell` drives the signal connected to it
+///
+/// The Yosys internal cells are looked up in `cell_port_directions`. The direction of the ports
+/// of other cells, instances of modules or vendor cells, is guessed from the names Yosys uses for
+/// outputs: `Y`, `Q`, `X` and `CO`.
+fn is_output_port(cell: &Cell, port: &str) -> bool {
+    if cell_port_directions(&cell.cell_type).is_some() {
+        cell.port_direction(port)
+            .is_some_and(|direction| direction != PortDir::Input)
+    } else {
+        matches!(port, "Y" | "Q" | "X" | "CO")
+    }
+}
+
+/// The clock port of `cell` if it is a flip-flop: a Yosys cell with a `Q` output and a `CLK` or
+/// `C` input
+fn clock_port(cell: &Cell) -> Option<&'static str> {
+    let table = cell_port_directions(&cell.cell_type)?;
+    if !table.contains(&("Q", PortDir::Output)) {
+        return None;
+    }
+    table
+        .iter()
+        .find(|(port, direction)| matches!(*port, "CLK" | "C") && *direction == PortDir::Input)
+        .map(|(port, _)| *port)
+}
+
+/// Whether `cell` is a Yosys register, a cell with a `Q` output: its output holds a state
+/// rather than following its inputs
+fn is_sequential(cell: &Cell) -> bool {
+    cell_port_directions(&cell.cell_type)
+        .is_some_and(|table| table.contains(&("Q", PortDir::Output)))
+}
+
+/// One bit of a signal
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum Bit {
+    /// a constant bit, one of `0 1 x z m -`
+    Const(char),
+    /// the bit at this index of a wire
+    Wire(String, usize),
+}
+
+impl Module {
+    /// The bits of the wires in `signal`, constants are skipped
+    pub(crate) fn wire_bits(&self, signal: &SigSpec) -> Vec<(String, usize)> {
+        self.signal_bits(signal)
+            .into_iter()
+            .filter_map(|bit| match bit {
+                Bit::Wire(name, index) => Some((name, index)),
+                Bit::Const(_) => None,
+            })
+            .collect()
+    }
+
+    /// Every bit of `signal`, least significant first
+    ///
+    /// A range only gives the bits its wire declares, an unknown wire is taken as one bit wide.
+    fn signal_bits(&self, signal: &SigSpec) -> Vec<Bit> {
+        match signal {
+            SigSpec::Constant(constant) => {
+                let bits = match constant {
+                    Constant::Value(bits) => bits.clone(),
+                    Constant::Fill(bit, width) => vec![*bit; *width],
+                    Constant::Integer(i) => match Constant::from_i64_width(i64::from(*i), 32) {
+                        Constant::Value(bits) => bits,
+                        _ => unreachable!(),
+                    },
+                    // the last character is the least significant byte
+                    Constant::String(s) => s
+                        .bytes()
+                        .rev()
+                        .flat_map(|byte| (0..8).map(move |i| (byte >> i) & 1))
+                        .map(|bit| if bit == 1 { '1' } else { '0' })
+                        .collect(),
+                    // a real is not a signal
+                    Constant::Real(_) => vec![],
+                };
+                bits.into_iter().map(Bit::Const).collect()
+            }
+            SigSpec::WireId(name) => {
+                let width = self.wire(name).map_or(1, |wire| wire.width);
+                (0..width).map(|bit| Bit::Wire(name.clone(), bit)).collect()
+            }
+            SigSpec::Range(inner, start, end) => {
+                let SigSpec::WireId(name) = inner.as_ref() else {
+                    return self.signal_bits(inner);
+                };
+                let width = self.wire(name).map_or(1, |wire| wire.width);
+                let end = end.unwrap_or(*start);
+                // the bits beyond the wire do not exist, `validate` reports them
+                (end.min(*start)..end.max(*start).saturating_add(1).min(width))
+                    .map(|bit| Bit::Wire(name.clone(), bit))
+                    .collect()
+            }
+            // the first part of a concatenation holds the most significant bits
+            SigSpec::Concat(parts) => parts
+                .iter()
+                .rev()
+                .flat_map(|part| self.signal_bits(part))
+                .collect(),
+        }
+    }
+
+    /// The shortest sigspec for `bits`, least significant first
+    fn bits_sigspec(&self, bits: Vec<Bit>) -> SigSpec {
+        let mut parts: Vec<SigSpec> = vec![];
+        for bit in bits {
+            match (parts.last_mut(), bit) {
+                (Some(SigSpec::Constant(Constant::Value(values))), Bit::Const(value)) => {
+                    values.push(value)
+                }
+                (Some(SigSpec::Range(run, start, end)), Bit::Wire(name, index))
+                    if **run == SigSpec::WireId(name.clone()) && index == *start + 1 =>
+                {
+                    *end = Some(end.unwrap_or(*start));
+                    *start = index;
+                }
+                (_, Bit::Const(value)) => parts.push(SigSpec::value(&value.to_string())),
+                (_, Bit::Wire(name, index)) => parts.push(SigSpec::range(name, index, None)),
+            }
+        }
+        // a range over all the bits of its wire is the wire
+        for part in &mut parts {
+            let SigSpec::Range(run, start, end) = part else {
+                continue;
+            };
+            let SigSpec::WireId(name) = run.as_ref() else {
+                continue;
+            };
+            let whole = self
+                .wire(name)
+                .is_some_and(|wire| end.unwrap_or(*start) == 0 && *start + 1 == wire.width);
+            if whole {
+                *part = SigSpec::wire(name.clone());
+            }
+        }
+        match parts.len() {
+            1 => parts.pop().unwrap(),
+            _ => SigSpec::Concat(parts.into_iter().rev().collect()),
+        }
+    }
+
+    /// The bits `hi` down to `lo` of `signal`, counted from 0 at its least significant bit
+    ///
+    /// `signal` may be any sigspec, a concatenation is sliced across its parts. `None` if `lo` is
+    /// above `hi`, `hi` is out of the signal or the signal has an unknown wire.
+    ///
+    /// ```
+    /// use rtlicious::SigSpec;
+    ///
+    /// let src = "module \\a\n  wire width 4 \\w\n  wire \\b\nend\n";
+    /// let design = rtlicious::parse(src).unwrap();
+    /// let module = &design.modules()["a"];
+    /// let signal = SigSpec::concat([SigSpec::wire("b"), SigSpec::wire("w")]);
+    /// assert_eq!(
+    ///     module.slice(&signal, 4, 3),
+    ///     Some(SigSpec::concat([SigSpec::wire("b"), SigSpec::range("w", 3, None)]))
+    /// );
+    /// assert_eq!(module.slice(&signal, 5, 0), None);
+    /// ```
+    pub fn slice(&self, signal: &SigSpec, hi: usize, lo: usize) -> Option<SigSpec> {
+        let width = self.sigspec_width(signal)?;
+        if lo > hi || hi >= width {
+            return None;
+        }
+        let bits = self.signal_bits(signal);
+        Some(self.bits_sigspec(bits[lo..=hi].to_vec()))
+    }
+
+    /// The flip-flop cells, by name: the Yosys cells clocked on an edge, `$dff`, `$adffe`,
+    /// `$_DFF_P_` and the like
+    pub fn dff_cells(&self) -> Vec<(&str, &Cell)> {
+        let mut cells: Vec<(&str, &Cell)> = self
+            .cells
+            .iter()
+            .filter(|(_, cell)| clock_port(cell).is_some())
+            .map(|(name, cell)| (name.as_str(), cell))
+            .collect();
+        cells.sort_by_key(|(name, _)| *name);
+        cells
+    }
+
+    /// The flip-flop cells grouped by the signal on their clock port, names sorted
+    ///
+    /// Signals are compared as written, a clock connected through a renaming `\connect` is a
+    /// domain of its own. Flip-flops with an unconnected clock are left out.
+    pub fn clock_domains(&self) -> HashMap<SigSpec, Vec<&str>> {
+        let mut domains: HashMap<SigSpec, Vec<&str>> = HashMap::new();
+        for (name, cell) in self.dff_cells() {
+            let clock = clock_port(cell).and_then(|port| cell.connections.get(port));
+            if let Some(clock) = clock {
+                domains.entry(clock.clone()).or_default().push(name);
+            }
+        }
+        domains
+    }
+
+    /// The indices in the HDL source of the first and last bits of `signal`, a wire or a range
+    /// over a wire, as `(start, end)`
+    ///
+    /// Ranges count bits from 0 as Yosys stores them, this applies the offset of the wire, and
+    /// flips the numbering of `upto` wires. `None` for other signals and unknown wires.
+    ///
+    /// ```
+    /// use rtlicious::SigSpec;
+    ///
+    /// let src = "module \\a\n  wire width 4 offset 8 \\w\n  wire width 4 upto \\u\nend\n";
+    /// let design = rtlicious::parse(src).unwrap();
+    /// let module = &design.modules()["a"];
+    /// assert_eq!(module.resolve_range(&SigSpec::wire("w")), Some((11, 8)));
+    /// assert_eq!(module.resolve_range(&SigSpec::range("w", 2, Some(1))), Some((10, 9)));
+    /// assert_eq!(module.resolve_range(&SigSpec::range("u", 0, None)), Some((3, 3)));
+    /// ```
+    pub fn resolve_range(&self, signal: &SigSpec) -> Option<(usize, usize)> {
+        let (name, start, end) = match signal {
+            SigSpec::WireId(name) => (name, None, None),
+            SigSpec::Range(inner, start, end) => match inner.as_ref() {
+                SigSpec::WireId(name) => (name, Some(*start), Some(end.unwrap_or(*start))),
+                _ => return None,
+            },
+            _ => return None,
+        };
+        let wire = self.wire(name)?;
+        let hdl = |bit: usize| {
+            if wire.upto {
+                wire.offset + wire.width.saturating_sub(bit + 1)
+            } else {
+                wire.offset + bit
+            }
+        };
+        let start = start.unwrap_or(wire.width.saturating_sub(1));
+        let end = end.unwrap_or(0);
+        Some((hdl(start), hdl(end)))
+    }
+
+    /// What drives each wire bit: input ports, connections, cell outputs and processes
+    ///
+    /// A process counts once for every bit it assigns or updates, however many times it does.
+    pub fn drivers(&self) -> HashMap<(String, usize), Vec<Driver>> {
+        let mut drivers: HashMap<(String, usize), Vec<Driver>> = HashMap::new();
+        let mut drive = |bits: Vec<(String, usize)>, driver: &Driver| {
+            for bit in bits {
+                drivers.entry(bit).or_default().push(driver.clone());
+            }
+        };
+        for (id, wire) in &self.wires {
+            if wire.input || wire.inout {
+                drive(self.wire_bits(&SigSpec::wire(id.inner())), &Driver::Port);
+            }
+        }
+        for (index, (dest, _)) in self.connections.iter().enumerate() {
+            drive(self.wire_bits(dest), &Driver::Connection(index));
+        }
+        for (name, cell) in &self.cells {
+            for (port, signal) in &cell.connections {
+                if is_output_port(cell, port) {
+                    let driver = Driver::Cell(name.clone(), port.clone());
+                    drive(self.wire_bits(signal), &driver);
+                }
+            }
+        }
+        for (name, process) in &self.processes {
+            let bits: BTreeSet<(String, usize)> = process
+                .destinations()
+                .into_iter()
+                .flat_map(|dest| self.wire_bits(dest))
+                .collect();
+            drive(bits.into_iter().collect(), &Driver::Process(name.clone()));
+        }
+        drivers
+    }
+
+    /// The wires with a bit driven by more than one source, sorted, inout wires excepted
+    ///
+    /// Blackboxes have none.
+    pub fn multiply_driven(&self) -> Vec<String> {
+        if self.is_blackbox() {
+            return vec![];
+        }
+        let wires: BTreeSet<String> = self
+            .drivers()
+            .into_iter()
+            .filter(|((name, _), drivers)| {
+                drivers.len() > 1 && !self.wire(name).is_some_and(|wire| wire.inout)
+            })
+            .map(|((name, _), _)| name)
+            .collect();
+        wires.into_iter().collect()
+    }
+
+    /// The number of ports reading each wire: cell inputs and the sources of module connections
+    ///
+    /// A port counts once per wire it reads, whatever bits of it and however nested in
+    /// concatenations. Every declared wire is a key, those never read count 0. Processes are not
+    /// ports and are not counted.
+    pub fn fanout_counts(&self) -> HashMap<String, usize> {
+        let mut counts: HashMap<String, usize> = self
+            .wires
+            .keys()
+            .map(|id| (id.inner().clone(), 0))
+            .collect();
+        let cell_inputs = self.cells.values().flat_map(|cell| {
+            cell.connections
+                .iter()
+                .filter(|(port, _)| !is_output_port(cell, port))
+                .map(|(_, signal)| signal)
+        });
+        let sources = self.connections.iter().map(|(_, src)| src);
+        for signal in cell_inputs.chain(sources) {
+            for name in signal.leaf_wires() {
+                *counts.entry(name.to_string()).or_default() += 1;
+            }
+        }
+        counts
+    }
+
+    /// The source bit of each wire bit a module connection drives
+    fn connection_sources(&self) -> HashMap<(String, usize), Bit> {
+        let mut sources = HashMap::new();
+        for (dest, src) in &self.connections {
+            let bits = self
+                .signal_bits(dest)
+                .into_iter()
+                .zip(self.signal_bits(src));
+            for (dest, src) in bits {
+                if let Bit::Wire(name, index) = dest {
+                    sources.insert((name, index), src);
+                }
+            }
+        }
+        sources
+    }
+
+    /// The cells whose outputs feed an input of each cell, following module connections
+    fn cell_fanin(&self) -> BTreeMap<&str, BTreeSet<&str>> {
+        let drivers = self.drivers();
+        let sources = self.connection_sources();
+        let mut fanin = BTreeMap::new();
+        for (name, cell) in &self.cells {
+            let mut inputs = BTreeSet::new();
+            for (port, signal) in &cell.connections {
+                if is_output_port(cell, port) {
+                    continue;
+                }
+                for mut bit in self.wire_bits(signal) {
+                    // a loop of connections has no cell at its end
+                    let mut seen = HashSet::new();
+                    while seen.insert(bit.clone()) {
+                        for driver in drivers.get(&bit).into_iter().flatten() {
+                            if let Driver::Cell(driving, _) = driver {
+                                inputs
+                                    .insert(self.cells.get_key_value(driving).unwrap().0.as_str());
+                            }
+                        }
+                        match sources.get(&bit) {
+                            Some(Bit::Wire(name, index)) => bit = (name.clone(), *index),
+                            _ => break,
+                        }
+                    }
+                }
+            }
+            fanin.insert(name.as_str(), inputs);
+        }
+        fanin
+    }
+
+    /// Follow the module connections back from `signal` to where its bits come from
+    ///
+    /// A wire bit is replaced by the source of the connection driving it, as long as that
+    /// connection is its only driver. Tracing stops at constants, ports, cell outputs, processes,
+    /// undriven or multiply driven bits, and at the first bit seen twice on a loop of connections.
+    ///
+    /// ```
+    /// use rtlicious::SigSpec;
+    ///
+    /// let src = "module \\m\n  wire \\a\n  wire \\b\n  connect \\b \\a\nend\n";
+    /// let design = rtlicious::parse(src).unwrap();
+    /// let module = &design.modules()["m"];
+    /// assert_eq!(module.trace_source(&SigSpec::wire("b")), SigSpec::wire("a"));
+    /// ```
+    pub fn trace_source(&self, signal: &SigSpec) -> SigSpec {
+        let drivers = self.drivers();
+        let sources = self.connection_sources();
+        let bits = self
+            .signal_bits(signal)
+            .into_iter()
+            .map(|mut bit| {
+                let mut seen = HashSet::new();
+                while let Bit::Wire(name, index) = &bit {
+                    let key = (name.clone(), *index);
+                    if !matches!(
+                        drivers.get(&key).map(Vec::as_slice),
+                        Some([Driver::Connection(_)])
+                    ) || !seen.insert(key.clone())
+                    {
+                        break;
+                    }
+                    // a source narrower than its destination leaves the upper bits without one
+                    let Some(source) = sources.get(&key) else {
+                        break;
+                    };
+                    bit = source.clone();
+                }
+                bit
+            })
+            .collect();
+        self.bits_sigspec(bits)
+    }
+
+    /// The names of the cells, each one after the cells driving its inputs
+    ///
+    /// Cells are ordered through the wires and connections linking them, processes are not
+    /// followed. Among cells free to go in any order, names are sorted. Registers are ordinary
+    /// cells here, so a feedback loop through one is a cycle too. Blackboxes have no cells to
+    /// order.
+    pub fn topological_cells(&self) -> Result<Vec<&str>, CycleError> {
+        if self.is_blackbox() {
+            return Ok(vec![]);
+        }
+        let mut fanin = self.cell_fanin();
+        let mut fanout: HashMap<&str, Vec<&str>> = HashMap::new();
+        for (cell, inputs) in &fanin {
+            for input in inputs {
+                fanout.entry(input).or_default().push(cell);
+            }
+        }
+        let mut ready: BTreeSet<&str> = fanin
+            .iter()
+            .filter(|(_, inputs)| inputs.is_empty())
+            .map(|(cell, _)| *cell)
+            .collect();
+        let mut order = Vec::with_capacity(fanin.len());
+        while let Some(cell) = ready.pop_first() {
+            order.push(cell);
+            fanin.remove(cell);
+            for output in fanout.get(cell).into_iter().flatten() {
+                let inputs = fanin.get_mut(output).unwrap();
+                inputs.remove(cell);
+                if inputs.is_empty() {
+                    ready.insert(output);
+                }
+            }
+        }
+        if fanin.is_empty() {
+            Ok(order)
+        } else {
+            Err(CycleError::new(
+                fanin.into_keys().map(str::to_string).collect(),
+            ))
+        }
+    }
+
+    /// The loops of cells whose inputs and outputs feed each other without a register between
+    ///
+    /// Cells are linked through the wires and connections between their ports, the port
+    /// directions coming from `cell_port_directions`. Registers, Yosys cells with a `Q` output
+    /// such as `$dff` or `$dlatch`, break the paths through them. Each loop lists its cells along
+    /// the signals, from the first one met in a depth first search by name. A cell feeding
+    /// itself is a loop of one. Blackboxes have none.
+    ///
+    /// ```
+    /// let src = "module \\m\n  wire \\a\n  wire \\b\n  cell $not $x\n    connect \\A \\a\n    \
+    ///            connect \\Y \\b\n  end\n  cell $not $y\n    connect \\A \\b\n    \
+    ///            connect \\Y \\a\n  end\nend\n";
+    /// let design = rtlicious::parse(src).unwrap();
+    /// assert_eq!(design.modules()["m"].combinational_loops(), [["x", "y"]]);
+    /// ```
+    pub fn combinational_loops(&self) -> Vec<Vec<String>> {
+        #[derive(Clone, Copy, PartialEq)]
+        enum Visit {
+            InProgress,
+            Done,
+        }
+        if self.is_blackbox() {
+            return vec![];
+        }
+        let mut fanout: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+        for (cell, inputs) in self.cell_fanin() {
+            fanout.entry(cell).or_default();
+            for input in inputs {
+                if !is_sequential(&self.cells[input]) {
+                    fanout.entry(input).or_default().push(cell);
+                }
+            }
+        }
+        let mut loops = vec![];
+        let mut visits: HashMap<&str, Visit> = HashMap::new();
+        for root in fanout.keys() {
+            if visits.contains_key(root) {
+                continue;
+            }
+            // iterative depth first search, the stack is the path from the root
+            let mut stack = vec![(*root, 0)];
+            visits.insert(root, Visit::InProgress);
+            while let Some((name, next)) = stack.pop() {
+                let Some(&child) = fanout[name].get(next) else {
+                    visits.insert(name, Visit::Done);
+                    continue;
+                };
+                stack.push((name, next + 1));
+                match visits.get(child) {
+                    Some(Visit::InProgress) => {
+                        let start = stack.iter().position(|(cell, _)| *cell == child).unwrap();
+                        loops.push(
+                            stack[start..]
+                                .iter()
+                                .map(|(cell, _)| cell.to_string())
+                                .collect(),
+                        );
+                    }
+                    Some(Visit::Done) => {}
+                    None => {
+                        visits.insert(child, Visit::InProgress);
+                        stack.push((child, 0));
+                    }
+                }
+            }
+        }
+        loops
+    }
+}
+
+impl Process {
+    /// The destinations of every assignment and update of the process
+    fn destinations(&self) -> Vec<&SigSpec> {
+        let mut destinations: Vec<&SigSpec> =
+            self.assignments.iter().map(|(dest, _)| dest).collect();
+        let mut switches: Vec<&Switch> = self.switches.iter().collect();
+        while let Some(switch) = switches.pop() {
+            for body in switch.cases.iter().flat_map(|case| &case.case_bodies) {
+                match body {
+                    CaseBody::Assign((dest, _)) => destinations.push(dest),
+                    CaseBody::Switch(switch) => switches.push(switch),
+                }
+            }
+        }
+        for sync in &self.syncs {
+            destinations.extend(sync.updates.iter().map(|update| &update.dest));
+        }
+        destinations
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use indoc::indoc;
+    use pretty_assertions::assert_eq;
+
+    const DRIVEN: &str = indoc! {r#"
+        module \m
+          wire input 1 \a
+          wire width 2 \b
+          wire width 2 \c
+          wire inout 2 \io
+          wire \d
+          wire \r
+          cell $not $n
+            connect \A \a
+            connect \Y \b [0]
+          end
+          cell $not $m
+            connect \A \a
+            connect \Y \d
+          end
+          connect \b [1] \a
+          connect \c { \a \a }
+          connect \c [0] \a
+          connect \io \a
+          connect \d \a
+          process $p
+            assign \r \a
+            switch \a
+              case 1'1
+                assign \r 1'0
+            end
+          end
+        end
+    "#};
+
+    #[test]
+    fn test_drivers() {
+        let design = parse(DRIVEN).unwrap();
+        let module = &design.modules()["m"];
+        let drivers = module.drivers();
+        assert_eq!(drivers[&("a".to_string(), 0)], vec![Driver::Port]);
+        assert_eq!(
+            drivers[&("b".to_string(), 0)],
+            vec![Driver::Cell("n".to_string(), "Y".to_string())]
+        );
+        assert_eq!(drivers[&("b".to_string(), 1)], vec![Driver::Connection(0)]);
+        assert_eq!(drivers[&("c".to_string(), 1)], vec![Driver::Connection(1)]);
+        assert_eq!(drivers[&("c".to_string(), 0)].len(), 2);
+        // the process assigns \r twice but counts once
+        assert_eq!(
+            drivers[&("r".to_string(), 0)],
+            vec![Driver::Process("p".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_drivers_range_beyond_wire() {
+        let input = "module \\m\n  wire \\a\n  wire \\b\n  connect \\a [5:0] \\b\n  connect \\b [2000000000:0] 1'0\nend\n";
+        let design = parse(input).unwrap();
+        let module = &design.modules()["m"];
+        let drivers = module.drivers();
+        // only the bits the wires declare are driven
+        assert_eq!(drivers.len(), 2);
+        assert_eq!(drivers[&("a".to_string(), 0)], vec![Driver::Connection(0)]);
+        assert_eq!(drivers[&("b".to_string(), 0)], vec![Driver::Connection(1)]);
+        assert!(module.multiply_driven().is_empty());
+        assert_eq!(
+            module.trace_source(&SigSpec::range("a", 0, None)),
+            SigSpec::value("0")
+        );
+    }
+
+    #[test]
+    fn test_fanout_counts() {
+        let input = "module \\m\n  wire \\a\n  cell $not $n\n    connect \\A \\a\n  end\n  \
+                     cell $and $m\n    connect \\A \\a\n    connect \\Y \\a\n  end\nend\n";
+        let design = parse(input).unwrap();
+        assert_eq!(design.modules()["m"].fanout_counts()["a"], 2);
+        let design = parse(DRIVEN).unwrap();
+        let counts = design.modules()["m"].fanout_counts();
+        // the inputs of $n and $m, and five connections, { \a \a } counting once
+        assert_eq!(counts["a"], 7);
+        assert_eq!(counts["b"], 0);
+        assert_eq!(counts["r"], 0);
+        assert_eq!(counts.len(), 6);
+    }
+
+    #[test]
+    fn test_clock_domains() {
+        let input = indoc! {r#"
+            module \m
+              wire \clk_a
+              wire \clk_b
+              wire width 2 \d
+              wire width 4 \q
+              cell $dff $r0
+                connect \CLK \clk_a
+                connect \D \d [0]
+                connect \Q \q [0]
+              end
+              cell $adff $r1
+                connect \CLK \clk_b
+                connect \ARST \d [1]
+                connect \D \d [1]
+                connect \Q \q [1]
+              end
+              cell $_DFF_P_ $r2
+                connect \C \clk_a
+                connect \D \d [1]
+                connect \Q \q [2]
+              end
+              cell $dlatch $l
+                connect \EN \clk_b
+                connect \D \d [0]
+                connect \Q \q [3]
+              end
+            end
+        "#};
+        let design = parse(input).unwrap();
+        let module = &design.modules()["m"];
+        let names: Vec<&str> = module.dff_cells().iter().map(|(name, _)| *name).collect();
+        assert_eq!(names, ["r0", "r1", "r2"]);
+        let domains = module.clock_domains();
+        assert_eq!(domains.len(), 2);
+        assert_eq!(domains[&SigSpec::wire("clk_a")], ["r0", "r2"]);
+        assert_eq!(domains[&SigSpec::wire("clk_b")], ["r1"]);
+    }
+
+    #[test]
+    fn test_topological_cells_single() {
+        let input = indoc! {r#"
+            module \comb_not1
+              wire $logic_not$vectors/comb_not1.v:7$2_Y
+              wire input 1 \a
+              wire output 2 \b
+              cell $logic_not $logic_not$vectors/comb_not1.v:7$2
+                parameter \A_SIGNED 0
+                parameter \A_WIDTH 1
+                parameter \Y_WIDTH 1
+                connect \A \a
+                connect \Y $logic_not$vectors/comb_not1.v:7$2_Y
+              end
+              connect \b $logic_not$vectors/comb_not1.v:7$2_Y
+            end
+        "#};
+        let design = parse(input).unwrap();
+        let module = &design.modules()["comb_not1"];
+        assert_eq!(
+            module.topological_cells().unwrap(),
+            vec!["logic_not$vectors/comb_not1.v:7$2"]
+        );
+    }
+
+    #[test]
+    fn test_topological_cells_chain() {
+        // \z is computed from \y through a connection, and named before it
+        let input = indoc! {r#"
+            module \chain
+              wire input 1 \a
+              wire width 2 \x
+              wire \y
+              wire output 2 \b
+              cell $not \z
+                connect \A \x [1]
+                connect \Y \b
+              end
+              cell $not \y
+                connect \A \a
+                connect \Y \y
+              end
+              connect \x { \y 1'0 }
+            end
+        "#};
+        let design = parse(input).unwrap();
+        let module = &design.modules()["chain"];
+        assert_eq!(module.topological_cells().unwrap(), vec!["y", "z"]);
+    }
+
+    #[test]
+    fn test_topological_cells_cycle() {
+        let input = indoc! {r#"
+            module \ring
+              wire \a
+              wire \b
+              wire \c
+              cell $not \n1
+                connect \A \a
+                connect \Y \b
+              end
+              cell $not \n2
+                connect \A \b
+                connect \Y \a
+              end
+              cell $not \n3
+                connect \A \a
+                connect \Y \c
+              end
+            end
+        "#};
+        let design = parse(input).unwrap();
+        let module = &design.modules()["ring"];
+        let e = module.topological_cells().unwrap_err();
+        assert_eq!(e.cells(), &vec!["n1", "n2", "n3"]);
+        assert_eq!(
+            e.to_string(),
+            "cells depend on each other in a loop: n1, n2, n3"
+        );
+    }
+
+    #[test]
+    fn test_combinational_loops() {
+        let input = indoc! {r#"
+            module \m
+              wire \a
+              wire \b
+              wire \c
+              wire \d
+              wire \q
+              wire \clk
+              cell $and \x
+                connect \A \a
+                connect \B \q
+                connect \Y \b
+              end
+              cell $not \y
+                connect \A \b
+                connect \Y \c
+              end
+              connect \a \c
+              cell $dff \r
+                parameter \WIDTH 1
+                connect \CLK \clk
+                connect \D \b
+                connect \Q \q
+              end
+              cell $not \z
+                connect \A \d
+                connect \Y \d
+              end
+            end
+        "#};
+        let design = parse(input).unwrap();
+        let module = &design.modules()["m"];
+        // the feedback through the register is no loop
+        assert_eq!(module.combinational_loops(), [vec!["x", "y"], vec!["z"]]);
+        assert!(module.topological_cells().is_err());
+        let design = parse(DRIVEN).unwrap();
+        assert!(design.modules()["m"].combinational_loops().is_empty());
+    }
+
+    #[test]
+    fn test_trace_source() {
+        let input = indoc! {r#"
+            module \m
+              wire width 4 input 1 \i
+              wire width 2 \b
+              wire width 2 \c
+              wire width 4 \d
+              wire \l1
+              wire \l2
+              wire \y
+              cell $not $n
+                connect \A \i [0]
+                connect \Y \y
+              end
+              connect \b \c
+              connect \c 2'10
+              connect \d { \b \i [2:1] }
+              connect \l1 \l2
+              connect \l2 \l1
+            end
+        "#};
+        let design = parse(input).unwrap();
+        let module = &design.modules()["m"];
+        let trace = |s: &str| module.trace_source(&SigSpec::try_from(s).unwrap());
+        assert_eq!(trace("\\b"), SigSpec::value("10"));
+        assert_eq!(trace("\\b [1]"), SigSpec::value("1"));
+        assert_eq!(
+            trace("\\d"),
+            SigSpec::concat([SigSpec::value("10"), SigSpec::range("i", 2, Some(1))])
+        );
+        assert_eq!(trace("{ \\d [3] \\y \\i }"), trace("{ 1'1 \\y \\i }"));
+        assert_eq!(trace("\\y"), SigSpec::wire("y"));
+        // stops on the loop
+        assert_eq!(trace("\\l1"), SigSpec::wire("l1"));
+    }
+
+    #[test]
+    fn test_trace_source_narrow_connect() {
+        let input = indoc! {r#"
+            module \m
+              wire width 2 \a
+              wire \b
+              connect \a \b
+            end
+        "#};
+        let design = parse(input).unwrap();
+        let module = &design.modules()["m"];
+        assert_eq!(
+            module.trace_source(&SigSpec::wire("a")),
+            SigSpec::concat([SigSpec::range("a", 1, None), SigSpec::wire("b")])
+        );
+    }
+
+    #[test]
+    fn test_multiply_driven() {
+        let design = parse(DRIVEN).unwrap();
+        let module = &design.modules()["m"];
+        // \c by two connections, \d by a cell output and a connection, \io is inout
+        assert_eq!(module.multiply_driven(), vec!["c", "d"]);
+    }
+
+    #[test]
+    fn test_slice() {
+        let design =
+            parse("module \\m\n  wire \\a\n  wire \\b\n  wire width 4 \\w\nend\n").unwrap();
+        let module = &design.modules()["m"];
+        let ab = SigSpec::try_from("{ \\a \\b }").unwrap();
+        assert_eq!(module.slice(&ab, 0, 0), Some(SigSpec::wire("b")));
+        assert_eq!(module.slice(&ab, 1, 1), Some(SigSpec::wire("a")));
+        assert_eq!(module.slice(&ab, 1, 0), Some(ab.clone()));
+        assert_eq!(module.slice(&ab, 2, 0), None);
+        assert_eq!(module.slice(&ab, 0, 1), None);
+        let signal = SigSpec::try_from("{ 2'10 \\w [2:1] }").unwrap();
+        assert_eq!(
+            module.slice(&signal, 2, 1),
+            Some(SigSpec::concat([
+                SigSpec::value("0"),
+                SigSpec::range("w", 2, None)
+            ]))
+        );
+        assert_eq!(module.slice(&SigSpec::wire("x"), 0, 0), None);
+    }
+
+    #[test]
+    fn test_blackbox() {
+        let input = indoc! {r#"
+            attribute \blackbox 1
+            module \bb
+              wire input 1 \a
+              wire output 2 \y
+              connect \y \a
+              connect \y \a
+              cell $not $n
+                connect \A \y
+                connect \Y \y
+              end
+            end
+        "#};
+        let design = parse(input).unwrap();
+        let module = &design.modules()["bb"];
+        assert!(module.is_blackbox());
+        assert_eq!(module.multiply_driven(), Vec::<String>::new());
+        assert_eq!(module.topological_cells(), Ok(vec![]));
+        assert_eq!(module.validate(), Ok(()));
+        let design = parse(DRIVEN).unwrap();
+        assert!(!design.modules()["m"].is_blackbox());
+    }
+}
@@ -0,0 +1,208 @@
+//! Parse options.
+//!
+//! The parsers are plain `fn(Span) -> IResult`, so the options of the ongoing parse are kept in a
+//! thread local rather than threaded through every one of them.
+
+use std::{
+    cell::RefCell,
+    collections::HashSet,
+    fmt,
+    sync::{Arc, Mutex},
+};
+
+use crate::{Diagnostic, DiagnosticSink, ParseOptions, Span};
+
+thread_local! {
+    static OPTIONS: RefCell<ParseOptions> = RefCell::new(ParseOptions::default());
+    /// The diagnostics of the ongoing parse already reported, by offset and message, as the
+    /// parsers backtrack over the same input
+    static REPORTED: RefCell<HashSet<(usize, String)>> = RefCell::new(HashSet::new());
+}
+
+/// Restores the options of the enclosing parse, even when unwinding
+struct Restore(Option<(ParseOptions, HashSet<(usize, String)>)>);
+
+impl Drop for Restore {
+    fn drop(&mut self) {
+        if let Some((options, reported)) = self.0.take() {
+            OPTIONS.with(|current| *current.borrow_mut() = options);
+            REPORTED.with(|current| *current.borrow_mut() = reported);
+        }
+    }
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        Self {
+            lenient: false,
+            max_value_width: 1 << 20,
+            diagnostics: None,
+            exact_values: false,
+            partial: false,
+        }
+    }
+}
+
+impl DiagnosticSink {
+    /// A sink calling `f` with each diagnostic
+    pub fn new(f: impl FnMut(Diagnostic) + Send + 'static) -> Self {
+        Self(Arc::new(Mutex::new(f)))
+    }
+}
+
+impl fmt::Debug for DiagnosticSink {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("DiagnosticSink(..)")
+    }
+}
+
+/// Sinks are equal when they are clones of each other
+impl PartialEq for DiagnosticSink {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for DiagnosticSink {}
+
+impl ParseOptions {
+    /// Run `f` with `self` as the options of the ongoing parse
+    pub(crate) fn scope<T>(&self, f: impl FnOnce() -> T) -> T {
+        let options = OPTIONS.with(|options| options.replace(self.clone()));
+        let reported = REPORTED.with(|reported| reported.take());
+        let _restore = Restore(Some((options, reported)));
+        f()
+    }
+
+    /// Report `message` at `at` to the diagnostics sink of the ongoing parse, or log it as a
+    /// warning if there is none
+    pub(crate) fn report(at: Span, message: impl Into<String>) {
+        let message = message.into();
+        let new = REPORTED.with(|reported| {
+            reported
+                .borrow_mut()
+                .insert((at.location_offset(), message.clone()))
+        });
+        if !new {
+            return;
+        }
+        let diagnostic = Diagnostic {
+            line: at.location_line(),
+            column: at.get_utf8_column(),
+            message,
+        };
+        // the options are not borrowed while the sink runs
+        match OPTIONS.with(|options| options.borrow().diagnostics.clone()) {
+            Some(sink) => {
+                let mut f = sink.0.lock().unwrap_or_else(|e| e.into_inner());
+                f(diagnostic)
+            }
+            None => log::warn!("{}", diagnostic),
+        }
+    }
+
+    /// Whether the ongoing parse is lenient
+    pub(crate) fn lenient() -> bool {
+        OPTIONS.with(|options| options.borrow().lenient)
+    }
+
+    /// The widest value accepted by the ongoing parse
+    pub(crate) fn max_value_width() -> usize {
+        OPTIONS.with(|options| options.borrow().max_value_width)
+    }
+
+    /// Whether the ongoing parse keeps values filled from a single bit as written
+    pub(crate) fn exact_values() -> bool {
+        OPTIONS.with(|options| options.borrow().exact_values)
+    }
+
+    /// Whether the ongoing parse reads a partial dump
+    pub(crate) fn partial() -> bool {
+        OPTIONS.with(|options| options.borrow().partial)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use crate::*;
+
+    #[test]
+    fn test_scope() {
+        assert!(!ParseOptions::lenient());
+        let lenient = ParseOptions {
+            lenient: true,
+            ..Default::default()
+        };
+        lenient.scope(|| {
+            assert!(ParseOptions::lenient());
+            ParseOptions::default().scope(|| assert!(!ParseOptions::lenient()));
+            assert!(ParseOptions::lenient());
+        });
+        assert!(!ParseOptions::lenient());
+    }
+
+    #[test]
+    fn test_diagnostics() {
+        let input = indoc::indoc! {r#"
+            module \m
+              wire \a width 2
+              wire b
+              cell $not $n
+                connect \a
+              end
+              foobar 1
+              connect \a [1:0] { \b \b }
+              process $p
+                switch \b
+                  case 1'1
+                    connect \b 1'0
+                end
+              end
+            end
+        "#};
+        let collected = Arc::new(Mutex::new(vec![]));
+        let sink = collected.clone();
+        let options = ParseOptions {
+            lenient: true,
+            diagnostics: Some(DiagnosticSink::new(move |diagnostic| {
+                sink.lock().unwrap().push(diagnostic)
+            })),
+            ..Default::default()
+        };
+        parse_with(input, &options).unwrap();
+        let diagnostics: Vec<String> = collected
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|diagnostic| diagnostic.to_string())
+            .collect();
+        assert_eq!(
+            diagnostics,
+            [
+                "line 2, column 10: wire options after the wire id",
+                "line 3, column 8: public id without its `\\`",
+                "line 5, column 5: cell connection without a port name",
+                "line 7, column 3: unknown statement kept in the module",
+                "line 12, column 9: connect statement in a case, read as an assignment",
+            ]
+        );
+
+        // a strict parse accepts no deviation, so it reports none
+        let strict = ParseOptions {
+            lenient: false,
+            ..options.clone()
+        };
+        assert!(parse_with(input, &strict).is_err());
+        assert_eq!(collected.lock().unwrap().len(), 5);
+        assert_eq!(options, options.clone());
+        assert_ne!(
+            options,
+            ParseOptions {
+                lenient: true,
+                ..Default::default()
+            }
+        );
+    }
+}
@@ -0,0 +1,130 @@
+//! Running transformation passes over a design.
+
+use std::collections::HashSet;
+
+use crate::*;
+
+impl Design {
+    /// Run `pass` over the design
+    pub fn apply(&mut self, pass: impl Pass) -> Result<(), PassError> {
+        pass.run(self)
+    }
+
+    /// Run `passes` over the design one after the other, stopping at the first that fails
+    ///
+    /// ```
+    /// use rtlicious::{Pass, RemoveUnusedWires};
+    ///
+    /// let src = "module \\m\n  wire \\a\n  wire \\b\n  wire \\c\n  connect \\a \\b\nend\n";
+    /// let mut design = rtlicious::parse(src).unwrap();
+    /// let passes: [&dyn Pass; 1] = [&RemoveUnusedWires];
+    /// design.apply_all(&passes).unwrap();
+    /// assert!(design.modules()["m"].wire("c").is_none());
+    /// ```
+    pub fn apply_all(&mut self, passes: &[&dyn Pass]) -> Result<(), PassError> {
+        passes.iter().try_for_each(|pass| pass.run(self))
+    }
+}
+
+impl<P: Pass + ?Sized> Pass for &P {
+    fn run(&self, design: &mut Design) -> Result<(), PassError> {
+        (**self).run(design)
+    }
+}
+
+impl Pass for RemoveUnusedWires {
+    fn run(&self, design: &mut Design) -> Result<(), PassError> {
+        for module in design.modules.values_mut() {
+            module.remove_unused_wires();
+        }
+        Ok(())
+    }
+}
+
+impl Module {
+    /// Remove the wires no signal of the module refers to, returning their names sorted
+    ///
+    /// Ports and wires with a true `\keep` attribute are kept, blackboxes are left as they are.
+    pub fn remove_unused_wires(&mut self) -> Vec<String> {
+        if self.is_blackbox() {
+            return vec![];
+        }
+        let used: HashSet<String> = self
+            .signals()
+            .into_iter()
+            .flat_map(|signal| signal.leaf_wires())
+            .map(str::to_string)
+            .collect();
+        let mut removed = vec![];
+        self.wires.retain(|id, wire| {
+            let keep = used.contains(id.inner())
+                || wire.port_id.is_some()
+                || wire.attribute_bool("keep") == Some(true);
+            if !keep {
+                removed.push(id.inner().clone());
+            }
+            keep
+        });
+        removed.sort();
+        removed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use indoc::indoc;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_remove_unused_wires() {
+        let input = indoc! {r#"
+            module \m
+              wire input 1 \a
+              wire output 2 \y
+              wire $unused
+              attribute \keep 1
+              wire \kept
+              wire width 2 \t
+              wire \dangling
+              cell $not $n
+                connect \A \a
+                connect \Y \t [0]
+              end
+              connect \y \t [0]
+            end
+        "#};
+        let mut design = parse(input).unwrap();
+        let mut expected = design.clone();
+        design.apply(RemoveUnusedWires).unwrap();
+        let removed = expected.module_mut("m").unwrap().remove_unused_wires();
+        assert_eq!(removed, ["dangling", "unused"]);
+        assert_eq!(design, expected);
+        let module = &design.modules()["m"];
+        let mut names: Vec<&str> = module
+            .wires()
+            .keys()
+            .map(|id| id.inner().as_str())
+            .collect();
+        names.sort();
+        assert_eq!(names, ["a", "kept", "t", "y"]);
+        assert_eq!(module.validate(), Ok(()));
+    }
+
+    #[test]
+    fn test_apply_all() {
+        struct Fail;
+        impl Pass for Fail {
+            fn run(&self, _: &mut Design) -> Result<(), PassError> {
+                Err(PassError::new("no"))
+            }
+        }
+        let mut design = parse("module \\m\n  wire \\a\nend\n").unwrap();
+        let e = design.apply_all(&[&Fail, &RemoveUnusedWires]).unwrap_err();
+        assert_eq!(e.to_string(), "no");
+        // the passes after the failing one do not run
+        assert!(design.modules()["m"].wire("a").is_some());
+        design.apply_all(&[&RemoveUnusedWires, &Fail]).unwrap_err();
+        assert!(design.modules()["m"].wire("a").is_none());
+    }
+}
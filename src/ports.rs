@@ -0,0 +1,104 @@
+//! The directions of the ports of the Yosys internal cells.
+//!
+//! Port names are those of the Yosys cell library, without sigil. Ports that a cell only has in
+//! some of its versions, the reset of `$memrd_v2` for instance, are listed for all of them.
+
+use crate::{PortDir, PortDirTable};
+
+use PortDir::{Input as I, Output as O};
+
+const UNARY: &PortDirTable = &[("A", I), ("Y", O)];
+const BINARY: &PortDirTable = &[("A", I), ("B", I), ("Y", O)];
+const MUX: &PortDirTable = &[("A", I), ("B", I), ("S", I), ("Y", O)];
+const SELECT: &PortDirTable = &[("A", I), ("S", I), ("Y", O)];
+const CHECK: &PortDirTable = &[("A", I), ("EN", I)];
+const SOURCE: &PortDirTable = &[("Y", O)];
+
+/// The port directions of the cell type `cell_type`, given without sigil
+pub(crate) fn table(cell_type: &str) -> Option<&'static PortDirTable> {
+    let table: &PortDirTable = match cell_type {
+        "not" | "pos" | "neg" | "reduce_and" | "reduce_or" | "reduce_xor" | "reduce_xnor"
+        | "reduce_bool" | "logic_not" | "slice" | "lut" | "sop" | "_BUF_" | "_NOT_" => UNARY,
+        "and" | "or" | "xor" | "xnor" | "shl" | "shr" | "sshl" | "sshr" | "shift" | "shiftx"
+        | "lt" | "le" | "eq" | "ne" | "eqx" | "nex" | "ge" | "gt" | "add" | "sub" | "mul"
+        | "macc" | "div" | "mod" | "divfloor" | "modfloor" | "pow" | "logic_and" | "logic_or"
+        | "concat" | "equiv" | "_AND_" | "_NAND_" | "_OR_" | "_NOR_" | "_XOR_" | "_XNOR_"
+        | "_ANDNOT_" | "_ORNOT_" => BINARY,
+        "mux" | "pmux" | "bwmux" | "_MUX_" | "_NMUX_" => MUX,
+        "bmux" | "demux" => SELECT,
+        "tribuf" => &[("A", I), ("EN", I), ("Y", O)],
+        "alu" => &[
+            ("A", I),
+            ("B", I),
+            ("CI", I),
+            ("BI", I),
+            ("X", O),
+            ("Y", O),
+            ("CO", O),
+        ],
+        "lcu" => &[("P", I), ("G", I), ("CI", I), ("CO", O)],
+        "fa" => &[("A", I), ("B", I), ("C", I), ("X", O), ("Y", O)],
+        "_AOI3_" | "_OAI3_" => &[("A", I), ("B", I), ("C", I), ("Y", O)],
+        "_AOI4_" | "_OAI4_" => &[("A", I), ("B", I), ("C", I), ("D", I), ("Y", O)],
+        "ff" => &[("D", I), ("Q", O)],
+        "dff" => &[("CLK", I), ("D", I), ("Q", O)],
+        "dffe" => &[("CLK", I), ("EN", I), ("D", I), ("Q", O)],
+        "adff" => &[("CLK", I), ("ARST", I), ("D", I), ("Q", O)],
+        "adffe" => &[("CLK", I), ("ARST", I), ("EN", I), ("D", I), ("Q", O)],
+        "sdff" => &[("CLK", I), ("SRST", I), ("D", I), ("Q", O)],
+        "sdffe" | "sdffce" => &[("CLK", I), ("SRST", I), ("EN", I), ("D", I), ("Q", O)],
+        "dffsr" => &[("CLK", I), ("SET", I), ("CLR", I), ("D", I), ("Q", O)],
+        "dffsre" => &[
+            ("CLK", I),
+            ("SET", I),
+            ("CLR", I),
+            ("EN", I),
+            ("D", I),
+            ("Q", O),
+        ],
+        "aldff" => &[("CLK", I), ("ALOAD", I), ("AD", I), ("D", I), ("Q", O)],
+        "aldffe" => &[
+            ("CLK", I),
+            ("ALOAD", I),
+            ("AD", I),
+            ("EN", I),
+            ("D", I),
+            ("Q", O),
+        ],
+        "dlatch" => &[("EN", I), ("D", I), ("Q", O)],
+        "adlatch" => &[("EN", I), ("ARST", I), ("D", I), ("Q", O)],
+        "dlatchsr" => &[("EN", I), ("SET", I), ("CLR", I), ("D", I), ("Q", O)],
+        "sr" => &[("SET", I), ("CLR", I), ("Q", O)],
+        "_DFF_P_" | "_DFF_N_" => &[("C", I), ("D", I), ("Q", O)],
+        "memrd" | "memrd_v2" => &[
+            ("CLK", I),
+            ("EN", I),
+            ("ARST", I),
+            ("SRST", I),
+            ("ADDR", I),
+            ("DATA", O),
+        ],
+        "memwr" | "memwr_v2" => &[("CLK", I), ("EN", I), ("ADDR", I), ("DATA", I)],
+        "meminit" | "meminit_v2" => &[("ADDR", I), ("DATA", I), ("EN", I)],
+        "mem" | "mem_v2" => &[
+            ("RD_CLK", I),
+            ("RD_EN", I),
+            ("RD_ARST", I),
+            ("RD_SRST", I),
+            ("RD_ADDR", I),
+            ("RD_DATA", O),
+            ("WR_CLK", I),
+            ("WR_EN", I),
+            ("WR_ADDR", I),
+            ("WR_DATA", I),
+        ],
+        "fsm" => &[("CLK", I), ("ARST", I), ("CTRL_IN", I), ("CTRL_OUT", O)],
+        "assert" | "assume" | "cover" | "live" | "fair" => CHECK,
+        "anyconst" | "anyseq" | "allconst" | "allseq" | "initstate" => SOURCE,
+        "specify2" => &[("EN", I), ("SRC", I), ("DST", I)],
+        "specify3" => &[("EN", I), ("SRC", I), ("DST", I), ("DAT", I)],
+        "specrule" => &[("SRC_EN", I), ("DST_EN", I), ("SRC", I), ("DST", I)],
+        _ => return None,
+    };
+    Some(table)
+}
@@ -0,0 +1,28 @@
+//! The types, traits and functions most uses of the crate need.
+//!
+//! ```
+//! use rtlicious::prelude::*;
+//!
+//! let src = r#"module \top
+//!   attribute \src "top.v:2.3-2.14"
+//!   wire input 1 \a
+//!   cell $not $n
+//!     connect \A \a
+//!   end
+//! end
+//! "#;
+//! let design: Design = parse(src).unwrap();
+//! let module: &Module = &design.modules()["top"];
+//! let a: &Wire = module.wire("a").unwrap();
+//! assert_eq!(a.attribute_str("src"), Some("top.v:2.3-2.14"));
+//! let cell: &Cell = &module.cells()["n"];
+//! assert_eq!(cell.connections()["A"], SigSpec::wire("a"));
+//! let e: ParseError = parse("module").unwrap_err();
+//! assert_eq!(*e.line(), 1);
+//! ```
+
+pub use crate::{
+    parse, parse_multi, parse_with, Case, CaseBody, Cell, Constant, Design, HasAttributes, Id,
+    Memory, Module, ParseError, ParseOptions, PortDir, Process, SigSpec, Switch, Sync, SyncOn,
+    Wire, WriteOptions,
+};
@@ -12,9 +12,81 @@
 //! ```
 
 use crate::*;
-use nom::{bytes::complete::tag, multi::many0, IResult};
+use nom::{bytes::complete::tag, multi::many0};
 use nom_tracable::tracable_parser;
 
+impl Process {
+    /// The updates of each sync of the process, grouped with the event triggering them
+    pub fn register_updates(&self) -> Vec<(SyncOn, Vec<(SigSpec, SigSpec)>)> {
+        self.syncs
+            .iter()
+            .map(|sync| (sync.sync_event.clone(), sync.update_pairs()))
+            .collect()
+    }
+
+    /// Like `register_updates`, restricted to syncs on a signal edge (`posedge`, `negedge` or `edge`)
+    pub fn clocked_updates(&self) -> Vec<(SyncOn, Vec<(SigSpec, SigSpec)>)> {
+        self.register_updates()
+            .into_iter()
+            .filter(|(event, _)| {
+                matches!(
+                    event,
+                    SyncOn::Signal(
+                        SignalSync::Posedge | SignalSync::Negedge | SignalSync::Edge,
+                        _
+                    )
+                )
+            })
+            .collect()
+    }
+
+    /// The flip-flop described by the process, in the shape `proc` gives to registers
+    ///
+    /// That is a single `sync posedge` or `sync negedge` with updates and no memory write, next
+    /// to an optional `sync init`. Any other sync, like an asynchronous reset, gives `None`.
+    pub fn infer_register(&self) -> Option<RegisterInfo> {
+        let mut clocked = None;
+        let mut init = vec![];
+        for sync in &self.syncs {
+            match &sync.sync_event {
+                SyncOn::Init => init.extend(sync.update_pairs()),
+                SyncOn::Signal(edge @ (SignalSync::Posedge | SignalSync::Negedge), clock)
+                    if clocked.is_none() && !sync.updates.is_empty() && sync.memwrs.is_empty() =>
+                {
+                    clocked = Some((clock, edge, sync));
+                }
+                _ => return None,
+            }
+        }
+        let (clock, edge, sync) = clocked?;
+        Some(RegisterInfo {
+            clock: clock.clone(),
+            edge: edge.clone(),
+            init,
+            updates: sync.update_pairs(),
+        })
+    }
+
+    /// Whether the process describes a flip-flop, see `infer_register`
+    pub fn is_ff(&self) -> bool {
+        self.infer_register().is_some()
+    }
+
+    /// The size and nesting of the switches of the process
+    pub fn complexity(&self) -> ProcessMetrics {
+        ProcessMetrics {
+            switch_depth: self.switches.iter().map(Switch::depth).max().unwrap_or(0),
+            case_count: self.switches.iter().map(Switch::case_count).sum(),
+            assign_count: self.assignments.len()
+                + self
+                    .switches
+                    .iter()
+                    .map(Switch::assign_count)
+                    .sum::<usize>(),
+        }
+    }
+}
+
 #[tracable_parser]
 pub(crate) fn process(input: Span) -> IResult<Span, (String, Process)> {
     let (input, _) = many0(characters::sep)(input)?;
@@ -57,9 +129,7 @@ pub(crate) fn process_end_stmt(input: Span) -> IResult<Span, &str> {
 pub(crate) fn assign_stmt(input: Span) -> IResult<Span, (SigSpec, SigSpec)> {
     let (input, _) = tag("assign")(input)?;
     let (input, _) = characters::sep(input)?;
-    let (input, dest) = sigspec::sigspec(input)?;
-    let (input, _) = characters::sep(input)?;
-    let (input, src) = sigspec::sigspec(input)?;
+    let (input, (dest, src)) = sigspec::sigspec_pair(input)?;
     let (input, _) = characters::eol(input)?;
     Ok((input, (dest, src)))
 }
@@ -141,6 +211,113 @@ mod tests {
         assert_eq!(process.0, "flatten\\bufreg.$proc$serv_bufreg.v:35$710");
         assert_eq!(process.1.attributes.len(), 1);
     }
+    #[test]
+    fn test_register_updates() {
+        let input = indoc! {r#"
+            process $proc$dff.v:3$1
+              assign $0\q[0:0] \d
+              sync posedge \clk
+                update \q $0\q[0:0]
+              sync init
+                update \q 1'0
+            end
+            "#};
+        let (_input, (_id, process)) = process(Span::new_extra(input, Default::default())).unwrap();
+        let clk = SigSpec::WireId("clk".to_string());
+        let q = SigSpec::WireId("q".to_string());
+        assert_eq!(
+            process.register_updates(),
+            vec![
+                (
+                    SyncOn::Signal(SignalSync::Posedge, clk.clone()),
+                    vec![(q.clone(), SigSpec::WireId("0\\q[0:0]".to_string()))]
+                ),
+                (
+                    SyncOn::Init,
+                    vec![(q.clone(), SigSpec::Constant(Constant::Value(vec!['0'])))]
+                ),
+            ]
+        );
+        assert_eq!(
+            process.clocked_updates(),
+            vec![(
+                SyncOn::Signal(SignalSync::Posedge, clk),
+                vec![(q, SigSpec::WireId("0\\q[0:0]".to_string()))]
+            )]
+        );
+    }
+
+    const BUFREG: &str = indoc! {r#"
+        attribute \src "serv_bufreg.v:35.4-44.7"
+        process $flatten\bufreg.$proc$serv_bufreg.v:35$710
+          assign { } { }
+          assign $flatten\bufreg.$0\data[29:0] \bufreg.data
+          assign $flatten\bufreg.$0\lsb[1:0] \bufreg.lsb
+          assign $flatten\bufreg.$0\c_r[0:0] $flatten\bufreg.$and$serv_bufreg.v:37$711_Y
+          attribute \src "serv_bufreg.v:39.7-40.62"
+          switch \bufreg.i_en
+            case 1'1
+              assign $flatten\bufreg.$0\data[29:0] { $flatten\bufreg.$ternary$serv_bufreg.v:40$713_Y \bufreg.data [29:1] }
+            case 
+          end
+          attribute \src "serv_bufreg.v:42.7-43.39"
+          switch $flatten\bufreg.$ternary$serv_bufreg.v:42$715_Y
+            case 1'1
+              assign $flatten\bufreg.$0\lsb[1:0] { $flatten\bufreg.$ternary$serv_bufreg.v:43$716_Y \bufreg.lsb [1] }
+            case 
+          end
+          sync posedge \bufreg.i_clk
+            update \bufreg.c_r $flatten\bufreg.$0\c_r[0:0]
+            update \bufreg.data $flatten\bufreg.$0\data[29:0]
+            update \bufreg.lsb $flatten\bufreg.$0\lsb[1:0]
+        end
+        "#};
+
+    #[test]
+    fn test_infer_register() {
+        let (_, (_, bufreg)) = process(Span::new_extra(BUFREG, Default::default())).unwrap();
+        assert!(bufreg.is_ff());
+        let register = bufreg.infer_register().unwrap();
+        assert_eq!(register.clock, SigSpec::wire("bufreg.i_clk"));
+        assert_eq!(register.edge, SignalSync::Posedge);
+        assert!(register.init.is_empty());
+        assert_eq!(register.updates.len(), 3);
+        assert_eq!(
+            register.updates[0],
+            (
+                SigSpec::wire("bufreg.c_r"),
+                SigSpec::wire("flatten\\bufreg.$0\\c_r[0:0]")
+            )
+        );
+
+        let input = indoc! {r#"
+            process $proc$dff.v:3$1
+              sync negedge \clk
+                update \q \d
+              sync init
+                update \q 1'0
+            end
+            "#};
+        let (_, (_, dff)) = process(Span::new_extra(input, Default::default())).unwrap();
+        let register = dff.infer_register().unwrap();
+        assert_eq!(register.edge, SignalSync::Negedge);
+        assert_eq!(
+            register.init,
+            vec![(SigSpec::wire("q"), SigSpec::value("0"))]
+        );
+
+        // an asynchronous reset, and logic without a clock
+        for input in [
+            "process $p\n  sync posedge \\clk\n    update \\q \\d\n  sync posedge \\rst\n    update \\q 1'0\nend\n",
+            "process $p\n  sync always\n    update \\q \\d\nend\n",
+            "process $p\n  assign \\q \\d\nend\n",
+        ] {
+            let (_, (_, process)) = process(Span::new_extra(input, Default::default())).unwrap();
+            assert_eq!(process.infer_register(), None, "{}", input);
+            assert!(!process.is_ff());
+        }
+    }
+
     #[test]
     fn test_proc_stmt() {
         let vectors = vec![
@@ -181,4 +358,100 @@ mod tests {
             assert_eq!(ret.1, expected);
         }
     }
+
+    #[test]
+    fn test_assign_stmt_concat_dest() {
+        let vectors = [
+            (
+                "assign { \\a \\b } \\src\n",
+                (
+                    SigSpec::concat([SigSpec::wire("a"), SigSpec::wire("b")]),
+                    SigSpec::wire("src"),
+                ),
+            ),
+            (
+                "assign { \\a [1:0] \\b }\t { \\c 1'0 }\n",
+                (
+                    SigSpec::concat([SigSpec::range("a", 1, Some(0)), SigSpec::wire("b")]),
+                    SigSpec::concat([SigSpec::wire("c"), SigSpec::value("0")]),
+                ),
+            ),
+            (
+                "assign { \\a \\b }\\src\n",
+                (
+                    SigSpec::concat([SigSpec::wire("a"), SigSpec::wire("b")]),
+                    SigSpec::wire("src"),
+                ),
+            ),
+            (
+                "assign \\a [3:2]{ \\b }\n",
+                (
+                    SigSpec::range("a", 3, Some(2)),
+                    SigSpec::concat([SigSpec::wire("b")]),
+                ),
+            ),
+        ];
+        for (input, expected) in vectors {
+            let span = Span::new_extra(input, Default::default());
+            let ret = assign_stmt(span);
+            assert!(ret.is_ok(), "failed: {}", input);
+            assert_eq!(ret.unwrap().1, expected, "failed: {}", input);
+        }
+        // a wire id runs until the next whitespace
+        let span = Span::new_extra("assign \\a\\b\n", Default::default());
+        assert!(assign_stmt(span).is_err());
+    }
+
+    #[test]
+    fn test_assign_concat_round_trip() {
+        let input = indoc! {r#"
+            module \m
+              wire width 2 \a
+              wire \b
+              wire width 3 \c
+              process \p
+                assign { \a \b } \c
+                assign { \b \a [0] } { \c [2] 1'1 }
+              end
+            end
+        "#};
+        let design = Design::new_from_str(input).unwrap();
+        let process = &design.modules()["m"].processes()["p"];
+        assert_eq!(
+            process.assignments[0].0,
+            SigSpec::concat([SigSpec::wire("a"), SigSpec::wire("b")])
+        );
+        assert_eq!(design.to_rtlil_string(), input);
+    }
+
+    #[test]
+    fn test_complexity() {
+        let input = indoc! {r#"
+            process $p
+              assign \a \b
+              switch 1'0
+                case 1'0
+                  switch \c
+                    case 1'1
+                      assign \a 1'1
+                      assign \b 1'0
+                    case 1'0
+                  end
+              end
+            end
+        "#};
+        let span = Span::new_extra(input, Default::default());
+        let (_, (_, parsed)) = process(span).unwrap();
+        assert_eq!(
+            parsed.complexity(),
+            ProcessMetrics {
+                switch_depth: 2,
+                case_count: 3,
+                assign_count: 3,
+            }
+        );
+        let span = Span::new_extra("process $p\nend\n", Default::default());
+        let (_, (_, parsed)) = process(span).unwrap();
+        assert_eq!(parsed.complexity(), ProcessMetrics::default());
+    }
 }
@@ -0,0 +1,259 @@
+//! Parsing what can be parsed of a design, skipping the statements that fail.
+
+use crate::module::BLOCKS;
+use crate::*;
+
+impl Design {
+    /// Parse a string into a `Design`, leaving out the statements that do not parse
+    ///
+    /// On an error, the failing statement is blanked out with its attributes and the input parsed
+    /// again. A statement fails as a whole, a cell with a broken connection is left out, from its
+    /// `cell` line to its `end`, as is a case with a broken line up to the next case. Lines keep
+    /// their numbers, so every error locates its statement in `input`. A design without any
+    /// module left is empty.
+    ///
+    /// Each error parses the input once more, this is meant for inputs with few errors.
+    pub fn new_recovering_from_str(input: &str) -> (Design, Vec<ParseError>) {
+        let mut lines: Vec<String> = input.split_inclusive('\n').map(str::to_string).collect();
+        let mut errors = vec![];
+        loop {
+            let text = lines.concat();
+            let e = match Design::new_from_str(&text) {
+                Ok(design) => return (design, errors),
+                Err(e) => e,
+            };
+            let line = *e.line() as usize - 1;
+            errors.push(e);
+            // the statement after the attributes the error may point at
+            let statement =
+                (line..lines.len()).find(|&line| keyword(&lines[line]) != Some("attribute"));
+            // a failure points at the inner line that failed, the whole block around it goes
+            let statement = statement.map(|line| enclosing_start(&lines, line));
+            let Some((start, end)) = statement.and_then(|line| {
+                let end = statement_end(&lines, line)?;
+                Some((attributes_start(&lines, line), end))
+            }) else {
+                // nothing left to blank out, ie. a module missing its `end`
+                return (Design::from_iter([]), errors);
+            };
+            lines[start.min(line)..=end].iter_mut().for_each(blank);
+        }
+    }
+}
+
+/// The first word of `line`
+fn keyword(line: &str) -> Option<&str> {
+    line.split_whitespace().next()
+}
+
+/// The first line of the innermost cell, process, switch or case around line `line`, or `line`
+/// if it starts a block itself or is directly in a module
+fn enclosing_start(lines: &[String], line: usize) -> usize {
+    let is_case = |index: usize| keyword(&lines[index]) == Some("case");
+    if is_case(line) || keyword(&lines[line]).is_some_and(|keyword| BLOCKS.contains(&keyword)) {
+        return line;
+    }
+    // the first lines of the open blocks, a case is open until the next case or `end`
+    let mut open: Vec<usize> = vec![];
+    for (index, text) in lines.iter().enumerate().take(line) {
+        match keyword(text) {
+            Some("end") => {
+                if open.last().is_some_and(|&start| is_case(start)) {
+                    open.pop();
+                }
+                open.pop();
+            }
+            Some("case") => {
+                if open.last().is_some_and(|&start| is_case(start)) {
+                    open.pop();
+                }
+                open.push(index);
+            }
+            Some(keyword) if BLOCKS.contains(&keyword) => open.push(index),
+            _ => {}
+        }
+    }
+    match open.last() {
+        Some(&start) if keyword(&lines[start]) != Some("module") => start,
+        _ => line,
+    }
+}
+
+/// The first of the attribute lines right before line `start`, or `start`
+fn attributes_start(lines: &[String], start: usize) -> usize {
+    (0..start)
+        .rev()
+        .take_while(|&line| keyword(&lines[line]) == Some("attribute"))
+        .last()
+        .unwrap_or(start)
+}
+
+/// The index of the last line of the statement starting at line `start`, `None` on an empty line
+///
+/// A block ends at its `end` line, or at the last line if it is not ended. A case ends before the
+/// next case of its switch, and its attributes, or before the `end` of the switch.
+fn statement_end(lines: &[String], start: usize) -> Option<usize> {
+    if keyword(&lines[start])? == "case" {
+        let mut depth = 0;
+        for (index, line) in lines.iter().enumerate().skip(start + 1) {
+            match keyword(line) {
+                Some("end") if depth == 0 => return Some(attributes_start(lines, index) - 1),
+                Some("case") if depth == 0 => return Some(attributes_start(lines, index) - 1),
+                Some("end") => depth -= 1,
+                Some(keyword) if BLOCKS.contains(&keyword) => depth += 1,
+                _ => {}
+            }
+        }
+        return Some(lines.len() - 1);
+    }
+    if !BLOCKS.contains(&keyword(&lines[start])?) {
+        return Some(start);
+    }
+    let mut depth = 0;
+    for (index, line) in lines.iter().enumerate().skip(start) {
+        match keyword(line) {
+            Some("end") => depth -= 1,
+            Some(keyword) if BLOCKS.contains(&keyword) => depth += 1,
+            _ => {}
+        }
+        if depth == 0 {
+            return Some(index);
+        }
+    }
+    Some(lines.len() - 1)
+}
+
+/// Replace the line by an empty line, keeping its line ending
+fn blank(line: &mut String) {
+    let ending = line.len() - line.trim_end_matches(['\n', '\r']).len();
+    line.replace_range(..line.len() - ending, "");
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use indoc::indoc;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_parse_recovering() {
+        let input = indoc! {r#"
+            module \a
+              wire $b
+              wire foo $c
+              wire $d
+              cell $not $n
+                connect \A $b
+                connect
+              end
+              connect $d $b
+            end
+        "#};
+        let (design, errors) = parse_recovering(input);
+        let lines: Vec<u32> = errors.iter().map(|e| *e.line()).collect();
+        assert_eq!(lines, [3, 5]);
+        assert_eq!(errors[0].snippet(), "  wire foo $c");
+        assert_eq!(errors[1].snippet(), "  cell $not $n");
+        let module = &design.modules()["a"];
+        assert_eq!(module.wires().len(), 2);
+        assert!(module.cells().is_empty());
+        assert_eq!(module.connections().len(), 1);
+    }
+
+    #[test]
+    fn test_parse_recovering_blocks() {
+        let input = indoc! {r#"
+            module \a
+              attribute \keep 1
+              cell foo
+                connect \A \b
+              end
+              wire \b
+            end
+        "#};
+        let (design, errors) = parse_recovering(input);
+        assert_eq!(errors.len(), 1);
+        assert_eq!(*errors[0].line(), 2);
+        let module = &design.modules()["a"];
+        assert!(module.cells().is_empty());
+        assert_eq!(module.wires().len(), 1);
+        let (design, errors) = parse_recovering("module \\a\n  wire \\b\n");
+        assert_eq!(errors.len(), 1);
+        assert!(design.modules().is_empty());
+        let input = "module \\a\nend\n";
+        assert_eq!(parse_recovering(input), (parse(input).unwrap(), vec![]));
+    }
+
+    #[test]
+    fn test_parse_recovering_inner_failure() {
+        // the connection fails on its own line, the whole cell is left out
+        let input = indoc! {r#"
+            module \a
+              wire $b
+              cell $not $n
+                connect \A 3'10
+                connect \Y $b
+              end
+            end
+        "#};
+        let (design, errors) = parse_recovering(input);
+        assert_eq!(errors.len(), 1);
+        assert_eq!(*errors[0].line(), 4);
+        let module = &design.modules()["a"];
+        assert!(module.cells().is_empty());
+        assert_eq!(module.wires().len(), 1);
+    }
+
+    #[test]
+    fn test_parse_recovering_too_large() {
+        // an index beyond an i32 fails the connection, it does not abort the parse
+        let input = indoc! {r#"
+            module \a
+              wire \b
+              connect \b [99999999999] 1'0
+              connect \b 1'1
+            end
+        "#};
+        let (design, errors) = parse_recovering(input);
+        assert_eq!(errors.len(), 1);
+        assert_eq!(*errors[0].line(), 3);
+        assert_eq!(
+            design.modules()["a"].connections(),
+            &vec![(SigSpec::wire("b"), SigSpec::value("1"))]
+        );
+    }
+
+    #[test]
+    fn test_parse_recovering_case() {
+        // the assignment of the broken case is not merged into the case before
+        let input = indoc! {r#"
+            module \a
+              wire $b
+              process $p
+                switch $b
+                  case 1'0
+                    assign $b 1'1
+                  attribute \src "a.v:1"
+                  case 3'10
+                    assign $b 1'0
+                  case 1'1
+                    assign $b $b
+                end
+              end
+            end
+        "#};
+        let (design, errors) = parse_recovering(input);
+        assert_eq!(errors.len(), 1);
+        let switch = &design.modules()["a"].processes()["p"].switches()[0];
+        assert_eq!(switch.cases().len(), 2);
+        assert_eq!(
+            switch.cases()[0].case_bodies(),
+            &vec![CaseBody::Assign((SigSpec::wire("b"), SigSpec::value("1")))]
+        );
+        assert!(switch.cases()[0].attributes().is_empty());
+        assert_eq!(
+            switch.cases()[1].compare_against(),
+            &Some(vec![SigSpec::value("1")])
+        );
+    }
+}
@@ -1,7 +1,7 @@
 use clap::{Parser, Subcommand};
 #[cfg(feature = "trace")]
 use nom_tracable::{cumulative_histogram, histogram};
-use std::{path::PathBuf, process};
+use std::{io, path::Path, path::PathBuf, process};
 
 #[derive(Parser)]
 #[command(version, about, long_about = None)]
@@ -14,17 +14,89 @@ struct Cli {
 enum Commands {
     #[command()]
     Parse(ParseOpts),
+    #[command()]
+    Dump(DumpOpts),
 }
 
 /// parse a file, returns error if it fails
 #[derive(Parser)]
 struct ParseOpts {
-    /// The input file to parse
+    /// The input file to parse, gzip compressed files are decompressed
     #[arg(short, long)]
     input: PathBuf,
     /// optionally print the parsed design to stdout
     #[arg(short, long)]
     print: bool,
+    /// skip the statements that do not parse and report them all, exits with 1 if there were any
+    #[arg(long)]
+    recover: bool,
+}
+
+/// parse a file and print it back as RTLIL to stdout
+#[derive(Parser)]
+struct DumpOpts {
+    /// The input file to parse, gzip compressed files are decompressed
+    #[arg(short, long)]
+    input: PathBuf,
+    /// only print the module with this name, without sigil
+    #[arg(short, long)]
+    module: Option<String>,
+}
+
+/// The magic number starting gzip streams
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+/// Read `path` to a string, decompressing it if it is gzip compressed
+fn read_input(path: &Path) -> io::Result<String> {
+    let bytes = std::fs::read(path)?;
+    if !bytes.starts_with(&GZIP_MAGIC) {
+        return String::from_utf8(bytes).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e));
+    }
+    #[cfg(feature = "gzip")]
+    {
+        use std::io::Read;
+        let mut text = String::new();
+        flate2::read::MultiGzDecoder::new(bytes.as_slice()).read_to_string(&mut text)?;
+        Ok(text)
+    }
+    #[cfg(not(feature = "gzip"))]
+    Err(io::Error::new(
+        io::ErrorKind::InvalidData,
+        "gzip compressed input, rebuild with the gzip feature",
+    ))
+}
+
+/// Read `path` to a string, exiting with 1 if it cannot be read
+fn read_input_or_exit(path: &Path) -> String {
+    match read_input(path) {
+        Ok(file) => file,
+        Err(e) => {
+            log::error!("could not read {}: {}", path.display(), e);
+            process::exit(1);
+        }
+    }
+}
+
+/// Read and parse `path`, exiting with 1 if it cannot be read or parsed
+fn read_design(path: &Path) -> rtlicious::Design {
+    let file = read_input_or_exit(path);
+    match rtlicious::parse(&file) {
+        Ok(design) => design,
+        Err(e) => exit_on_parse_error(path, &e),
+    }
+}
+
+/// Log where the parser stopped in `path` and exit with 1
+fn exit_on_parse_error(path: &Path, e: &rtlicious::ParseError) -> ! {
+    log::error!(
+        "The parser could not advance further than the element beginning here, we couldn't parse it or a child element: {}:{}:{}",
+        path.file_name().unwrap().to_str().unwrap(),
+        e.line(),
+        e.column()
+    );
+    log::error!("  {}", e.snippet());
+    log::error!("  {}", e.caret());
+    process::exit(1);
 }
 
 fn main() {
@@ -33,54 +105,42 @@ fn main() {
 
     match args.command {
         Commands::Parse(opts) => {
-            let file = std::fs::read_to_string(opts.input.clone()).unwrap();
-            let ret = rtlicious::parse(&file);
-            if let Err(e) = ret {
-                //let safe_rem: Vec<String> = e.lines().take(5).map(|l| l.to_string()).collect();
-                //log::error!("Failed to parse RTLIL file, the element we were unable to parse starts like this: \n {}", safe_rem.join("\n"));
+            let file = read_input_or_exit(&opts.input);
+            let file_name = opts.input.file_name().unwrap().to_str().unwrap();
+            let (design, errors) = if opts.recover {
+                rtlicious::parse_recovering(&file)
+            } else {
+                match rtlicious::parse(&file) {
+                    Ok(design) => (design, vec![]),
+                    Err(e) => exit_on_parse_error(&opts.input, &e),
+                }
+            };
+            for e in &errors {
                 log::error!(
-                    "The parser could not advance furter than the element begining here, we couldn't parse it or a child element: {}:{} :",
-                    opts.input.file_name().unwrap().to_str().unwrap(),
-                    e.location_line()
+                    "Skipped the statement beginning here, we couldn't parse it or a child element: {}:{}:{}",
+                    file_name,
+                    e.line(),
+                    e.column()
                 );
-                // get line content:
-                dbg!(e.location_line());
-                let loc = e.location_offset();
-                dbg!(loc);
-                let line = file
-                    .chars()
-                    .skip(loc)
-                    .skip_while(|c| *c != '\n')
-                    .skip(1)
-                    .take_while(|c| *c != '\n')
-                    .collect::<String>();
-                log::error!("  {}", line);
-
-                process::exit(1);
+                log::error!("  {}", e.snippet());
+                log::error!("  {}", e.caret());
             }
-            let design = ret.unwrap();
             if opts.print {
                 println!("{:#?}", design);
             }
-            log::info!("Parsed RTLIL file successfully");
-            log::info!("stats:");
-            let modules = design.modules().len();
-            let top_module_id = design
-                .modules()
-                .iter()
-                .find(|(_, m)| m.attributes().contains_key("top"));
-            log::info!("  modules: {}", modules);
-            if let Some((id, _)) = top_module_id {
-                log::info!("  top: {}", id);
+            if errors.is_empty() {
+                log::info!("Parsed RTLIL file successfully");
+            } else {
+                log::error!("{} statement(s) could not be parsed", errors.len());
             }
-            let mut wires = 0;
-            let mut cells = 0;
-            for module in design.modules() {
-                wires += module.1.wires().len();
-                cells += module.1.cells().len();
+            log::info!("stats:");
+            let stats = design.statistics();
+            log::info!("  modules: {}", stats.modules);
+            if let Some(top) = stats.top {
+                log::info!("  top: {}", top);
             }
-            log::info!("  wires: {}", wires);
-            log::info!("  cells: {}", cells);
+            log::info!("  wires: {}", stats.wires);
+            log::info!("  cells: {}", stats.cells);
 
             // Show histogram
             #[cfg(feature = "trace")]
@@ -88,6 +148,24 @@ fn main() {
                 histogram();
                 cumulative_histogram();
             }
+
+            if !errors.is_empty() {
+                process::exit(1);
+            }
+        }
+        Commands::Dump(opts) => {
+            let design = read_design(&opts.input);
+            let Some(name) = opts.module else {
+                print!("{}", design.to_rtlil_string());
+                return;
+            };
+            match design.module_to_rtlil_string(&name) {
+                Some(module) => print!("{}", module),
+                None => {
+                    log::error!("no module named {} in {}", name, opts.input.display());
+                    process::exit(1);
+                }
+            }
         }
     }
 }
@@ -0,0 +1,27 @@
+//! Printing a design, or one of its modules, with `dump`
+
+use std::process::Command;
+
+fn dump(args: &[&str]) -> (Option<i32>, String) {
+    let output = Command::new(env!("CARGO_BIN_EXE_rtlicious-cli"))
+        .args(["dump", "--input", "tests/fixtures/two_modules.il"])
+        .args(args)
+        .env("RUST_LOG", "off")
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    (output.status.code(), stdout)
+}
+
+#[test]
+fn test_dump() {
+    let fixture = include_str!("fixtures/two_modules.il");
+    assert_eq!(dump(&[]), (Some(0), fixture.to_string()));
+    let top = fixture.split_once("end\n").unwrap().1;
+    assert_eq!(dump(&["--module", "top"]), (Some(0), top.to_string()));
+}
+
+#[test]
+fn test_dump_missing_module() {
+    assert_eq!(dump(&["--module", "missing"]), (Some(1), String::new()));
+}
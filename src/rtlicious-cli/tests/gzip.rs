@@ -0,0 +1,24 @@
+//! Reading gzip compressed designs, ie. written by `yosys -p "write_rtlil design.il.gz"`
+
+use std::process::Command;
+
+#[test]
+#[cfg(feature = "gzip")]
+fn test_parse_gzip() {
+    let status = Command::new(env!("CARGO_BIN_EXE_rtlicious-cli"))
+        .args(["parse", "--input", "tests/fixtures/comb_not1.il.gz"])
+        .env("RUST_LOG", "error")
+        .status()
+        .unwrap();
+    assert!(status.success());
+}
+
+#[test]
+fn test_parse_missing_file() {
+    let status = Command::new(env!("CARGO_BIN_EXE_rtlicious-cli"))
+        .args(["parse", "--input", "tests/fixtures/missing.il"])
+        .env("RUST_LOG", "off")
+        .status()
+        .unwrap();
+    assert_eq!(status.code(), Some(1));
+}
@@ -0,0 +1,36 @@
+//! Reporting the statements skipped by `parse --recover`
+
+use std::process::Command;
+
+#[test]
+fn test_parse_recover() {
+    let output = Command::new(env!("CARGO_BIN_EXE_rtlicious-cli"))
+        .args([
+            "parse",
+            "--recover",
+            "--input",
+            "tests/fixtures/broken_wire.il",
+        ])
+        .env("RUST_LOG", "info")
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(1));
+    let log = String::from_utf8_lossy(&output.stdout) + String::from_utf8_lossy(&output.stderr);
+    assert!(log.contains("broken_wire.il:3:3"), "{}", log);
+    assert!(
+        log.contains("1 statement(s) could not be parsed"),
+        "{}",
+        log
+    );
+    assert!(log.contains("cells: 1"), "{}", log);
+}
+
+#[test]
+fn test_parse_without_recover() {
+    let status = Command::new(env!("CARGO_BIN_EXE_rtlicious-cli"))
+        .args(["parse", "--input", "tests/fixtures/broken_wire.il"])
+        .env("RUST_LOG", "off")
+        .status()
+        .unwrap();
+    assert_eq!(status.code(), Some(1));
+}
@@ -11,17 +11,291 @@
 //!            |  { <sigspec>* }
 //! ```
 
-use crate::{characters, constant, identifier, value, SigSpec, Span};
+use crate::{
+    characters, constant, error, identifier, value, Constant, IResult, NomError, ParseError,
+    SigSpec, SigSpecKind, Span,
+};
 use nom::{
     branch::alt,
     bytes::complete::{tag, take_while},
     combinator::{map, opt},
+    error::{ErrorKind, ParseError as _},
     multi::many0,
     sequence::terminated,
-    IResult,
 };
 use nom_tracable::tracable_parser;
 
+impl SigSpec {
+    /// All the bits of the wire `name`, given without its sigil
+    pub fn wire(name: impl Into<String>) -> SigSpec {
+        SigSpec::WireId(name.into())
+    }
+
+    /// The bits `start` (to `end`) of the wire `name`, ie. `\\a [3:0]`
+    pub fn range(name: impl Into<String>, start: usize, end: Option<usize>) -> SigSpec {
+        SigSpec::Range(Box::new(SigSpec::wire(name)), start, end)
+    }
+
+    /// The concatenation of `parts`, most significant first as in `{ \\a \\b }`
+    ///
+    /// ```
+    /// use rtlicious::SigSpec;
+    ///
+    /// let sigspec = SigSpec::concat([SigSpec::range("a", 3, Some(0)), SigSpec::value("1010")]);
+    /// assert_eq!(sigspec, SigSpec::try_from("{ \\a [3:0] 4'1010 }").unwrap());
+    /// ```
+    pub fn concat(parts: impl IntoIterator<Item = SigSpec>) -> SigSpec {
+        SigSpec::Concat(parts.into_iter().collect())
+    }
+
+    /// A constant
+    pub fn constant(constant: Constant) -> SigSpec {
+        SigSpec::Constant(constant)
+    }
+
+    /// A constant value from its bits written most significant first, ie. `"1010"` for `4'1010`
+    pub fn value(bits: &str) -> SigSpec {
+        SigSpec::Constant(Constant::Value(bits.chars().rev().collect()))
+    }
+
+    /// The bits of a constant value, least significant first, `None` for any other sigspec
+    ///
+    /// Concatenations of constants are not folded, neither are integers nor strings converted.
+    ///
+    /// ```
+    /// use rtlicious::{Constant, SigSpec};
+    ///
+    /// let sigspec = SigSpec::Constant(Constant::Value(vec!['0', '1', 'x']));
+    /// assert_eq!(sigspec.constant_value(), Some(&['0', '1', 'x'][..]));
+    /// assert_eq!(SigSpec::value("10").constant_value(), Some(&['0', '1'][..]));
+    /// assert_eq!(SigSpec::wire("a").constant_value(), None);
+    /// assert_eq!(SigSpec::concat([SigSpec::value("1")]).constant_value(), None);
+    /// ```
+    pub fn constant_value(&self) -> Option<&[char]> {
+        match self {
+            SigSpec::Constant(Constant::Value(bits)) => Some(bits),
+            _ => None,
+        }
+    }
+
+    /// The variant of the sigspec, without its contents
+    ///
+    /// `SigSpec` may gain variants, so matches over it outside this crate need a wildcard arm.
+    ///
+    /// ```
+    /// use rtlicious::{SigSpec, SigSpecKind};
+    ///
+    /// assert_eq!(SigSpec::wire("a").kind(), SigSpecKind::WireId);
+    /// assert_eq!(SigSpec::range("a", 1, None).kind(), SigSpecKind::Range);
+    /// assert_eq!(SigSpec::concat([]).kind(), SigSpecKind::Concat);
+    /// ```
+    pub fn kind(&self) -> SigSpecKind {
+        match self {
+            SigSpec::Constant(_) => SigSpecKind::Constant,
+            SigSpec::WireId(_) => SigSpecKind::WireId,
+            SigSpec::Range(..) => SigSpecKind::Range,
+            SigSpec::Concat(_) => SigSpecKind::Concat,
+        }
+    }
+
+    /// The id of a plain wire, `None` for any other sigspec, a range of a wire included
+    ///
+    /// ```
+    /// use rtlicious::SigSpec;
+    ///
+    /// assert_eq!(SigSpec::wire("a").as_wire_id(), Some("a"));
+    /// assert_eq!(SigSpec::range("a", 0, None).as_wire_id(), None);
+    /// ```
+    pub fn as_wire_id(&self) -> Option<&str> {
+        match self {
+            SigSpec::WireId(id) => Some(id),
+            _ => None,
+        }
+    }
+
+    /// Whether the sigspec is a plain wire
+    ///
+    /// ```
+    /// use rtlicious::SigSpec;
+    ///
+    /// assert!(SigSpec::wire("a").is_wire());
+    /// assert!(!SigSpec::concat([SigSpec::wire("a")]).is_wire());
+    /// ```
+    pub fn is_wire(&self) -> bool {
+        matches!(self, SigSpec::WireId(_))
+    }
+
+    /// Whether the sigspec is a constant
+    ///
+    /// ```
+    /// use rtlicious::SigSpec;
+    ///
+    /// assert!(SigSpec::value("10").is_constant());
+    /// assert!(!SigSpec::wire("a").is_constant());
+    /// ```
+    pub fn is_constant(&self) -> bool {
+        matches!(self, SigSpec::Constant(_))
+    }
+
+    /// Whether the sigspec is a concatenation, even of a single signal
+    ///
+    /// ```
+    /// use rtlicious::SigSpec;
+    ///
+    /// assert!(SigSpec::concat([SigSpec::wire("a")]).is_concat());
+    /// assert!(!SigSpec::wire("a").is_concat());
+    /// ```
+    pub fn is_concat(&self) -> bool {
+        matches!(self, SigSpec::Concat(_))
+    }
+
+    /// Visit this sigspec and every sigspec nested in it, parents before their children
+    ///
+    /// Nested sigspecs are visited in order, with a stack of their own rather than recursion, so
+    /// that deeply nested concatenations do not overflow the call stack.
+    pub fn walk<'a>(&'a self, f: &mut impl FnMut(&'a SigSpec)) {
+        let mut stack = vec![self];
+        while let Some(sigspec) = stack.pop() {
+            f(sigspec);
+            match sigspec {
+                SigSpec::Constant(_) | SigSpec::WireId(_) => {}
+                SigSpec::Range(inner, _, _) => stack.push(inner),
+                SigSpec::Concat(parts) => stack.extend(parts.iter().rev()),
+            }
+        }
+    }
+
+    /// The names of the wires the sigspec refers to, without sigil, each once in the order they
+    /// first appear
+    ///
+    /// ```
+    /// use rtlicious::SigSpec;
+    ///
+    /// let sigspec = SigSpec::try_from("{ \\a [3:2] \\b [0] \\a [0] 1'1 }").unwrap();
+    /// assert_eq!(sigspec.leaf_wires(), ["a", "b"]);
+    /// ```
+    pub fn leaf_wires(&self) -> Vec<&str> {
+        let mut wires: Vec<&str> = vec![];
+        self.walk(&mut |sigspec| {
+            if let SigSpec::WireId(name) = sigspec {
+                if !wires.contains(&name.as_str()) {
+                    wires.push(name);
+                }
+            }
+        });
+        wires
+    }
+
+    /// Refer to the wire `to` wherever the wire `from` is, names are given without their sigil
+    pub fn replace_wire(&mut self, from: &str, to: &str) {
+        match self {
+            SigSpec::Constant(_) => {}
+            SigSpec::WireId(name) => {
+                if name == from {
+                    *name = to.to_string();
+                }
+            }
+            SigSpec::Range(inner, _, _) => inner.replace_wire(from, to),
+            SigSpec::Concat(parts) => parts
+                .iter_mut()
+                .for_each(|part| part.replace_wire(from, to)),
+        }
+    }
+
+    /// Read a wire id ending in a range, `\a[3:0]` or `\a[2]`, as that range of the wire before it
+    ///
+    /// Yosys writes a space before the ranges it means, an id such as `$0\q[3:0]` is the name of
+    /// a wire: only split the ids of inputs written without that space, where no wire is named so.
+    /// Ids are split in nested sigspecs too, others are left as is.
+    ///
+    /// ```
+    /// use rtlicious::SigSpec;
+    ///
+    /// let mut sigspec = SigSpec::wire("a[3:0]");
+    /// sigspec.split_trailing_range();
+    /// assert_eq!(sigspec, SigSpec::range("a", 3, Some(0)));
+    /// ```
+    pub fn split_trailing_range(&mut self) {
+        match self {
+            SigSpec::Constant(_) => {}
+            SigSpec::WireId(name) => {
+                if let Some((wire, start, end)) = trailing_range(name) {
+                    *self = SigSpec::range(wire, start, end);
+                }
+            }
+            SigSpec::Range(inner, _, _) => inner.split_trailing_range(),
+            SigSpec::Concat(parts) => parts.iter_mut().for_each(SigSpec::split_trailing_range),
+        }
+    }
+
+    /// Refer to the wire `f(name)` wherever the wire `name` is, names are given without their
+    /// sigil
+    ///
+    /// ```
+    /// use std::collections::HashMap;
+    /// use rtlicious::SigSpec;
+    ///
+    /// let renames = HashMap::from([("a", "x")]);
+    /// let sigspec = SigSpec::try_from("{ \\a \\b [1] }").unwrap();
+    /// let sigspec = sigspec.map_wires(&mut |name| renames.get(name).unwrap_or(&name).to_string());
+    /// assert_eq!(sigspec, SigSpec::try_from("{ \\x \\b [1] }").unwrap());
+    /// ```
+    pub fn map_wires(self, f: &mut impl FnMut(&str) -> String) -> SigSpec {
+        match self {
+            SigSpec::Constant(_) => self,
+            SigSpec::WireId(name) => SigSpec::WireId(f(&name)),
+            SigSpec::Range(inner, start, end) => {
+                SigSpec::Range(Box::new(inner.map_wires(f)), start, end)
+            }
+            SigSpec::Concat(parts) => {
+                SigSpec::Concat(parts.into_iter().map(|part| part.map_wires(f)).collect())
+            }
+        }
+    }
+}
+
+/// The wire and the indices of an id ending in `[start:end]` or `[start]`
+fn trailing_range(name: &str) -> Option<(&str, usize, Option<usize>)> {
+    let (wire, range) = name.strip_suffix(']')?.rsplit_once('[')?;
+    if wire.is_empty() {
+        return None;
+    }
+    let index = |digits: &str| {
+        digits
+            .bytes()
+            .all(|c| c.is_ascii_digit())
+            .then(|| digits.parse().ok())
+            .flatten()
+    };
+    match range.split_once(':') {
+        Some((start, end)) => Some((wire, index(start)?, Some(index(end)?))),
+        None => Some((wire, index(range)?, None)),
+    }
+}
+
+impl TryFrom<&str> for SigSpec {
+    type Error = ParseError;
+
+    /// Parse a sigspec from its textual form
+    ///
+    /// ```
+    /// use rtlicious::{Constant, SigSpec};
+    ///
+    /// let sigspec = SigSpec::try_from("{ \\a [3:0] 4'1010 }").unwrap();
+    /// assert_eq!(
+    ///     sigspec,
+    ///     SigSpec::Concat(vec![
+    ///         SigSpec::Range(Box::new(SigSpec::WireId("a".to_string())), 3, Some(0)),
+    ///         SigSpec::Constant(Constant::Value(vec!['0', '1', '0', '1'])),
+    ///     ])
+    /// );
+    /// assert!(SigSpec::try_from("{ \\a").is_err());
+    /// ```
+    fn try_from(input: &str) -> Result<Self, Self::Error> {
+        error::parse_complete(input, sigspec)
+    }
+}
+
 /// ```text
 /// <sigspec> ::= <constant>
 ///            |  <wire-id>
@@ -42,28 +316,59 @@ pub(crate) fn sigspec(input: Span) -> IResult<Span, SigSpec> {
 }
 
 /// `<wire_id> [ <integer> (:<integer>)? ]`
+///
+/// The brackets, the colon and the integers are tokens of their own and may be separated by
+/// spaces or tabs, ie. `\\a [ 3 : 0 ]`.
+/// The separator between the wire id and `[` is required: an id runs until the next whitespace,
+/// so `\\a[3:0]` is the wire named `a[3:0]`, as Yosys reads it and as it names the wires it
+/// generates (`$0\\b[0:0]`). A negative index is a failure of kind `Verify`.
 pub(crate) fn sigspec_range(input: Span) -> IResult<Span, (SigSpec, usize, Option<usize>)> {
+    let seps = || take_while(characters::is_sep);
     // get the wire_id
     let (input, wire_id) = identifier::id(input)?;
     // consume the whitespace
     let (input, _) = characters::sep(input)?;
     // consume the '['
-    let (input, _) = tag("[")(input)?;
+    let (input, _) = terminated(tag("["), seps())(input)?;
     // consume range
-    let (input, start) = value::integer(input)?;
+    let (input, start) = terminated(index, seps())(input)?;
     // :
-    let (input, has_range) = opt(tag(":"))(input)?;
+    let (input, has_range) = opt(terminated(tag(":"), seps()))(input)?;
     let (input, opt_end) = has_range.map_or(Ok((input, None)), |_drop| {
-        let (input, end) = value::integer(input)?;
-        let end = end as usize;
+        let (input, end) = terminated(index, seps())(input)?;
         Ok((input, Some(end)))
     })?;
     // consume the ']'
     let (input, _) = tag("]")(input)?;
-    Ok((
-        input,
-        (SigSpec::WireId(wire_id.erease()), start as usize, opt_end),
-    ))
+    Ok((input, (SigSpec::WireId(wire_id.erease()), start, opt_end)))
+}
+
+/// An index of a range, a non-negative `<integer>`
+fn index(input: Span) -> IResult<Span, usize> {
+    let start = input;
+    let (input, index) = value::integer(input)?;
+    match usize::try_from(index) {
+        Ok(index) => Ok((input, index)),
+        Err(_) => Err(nom::Err::Failure(NomError::from_error_kind(
+            start,
+            ErrorKind::Verify,
+        ))),
+    }
+}
+
+/// `<dest-sigspec> <src-sigspec>`, as `connect`, `assign` and `update` take them
+///
+/// `}` and `]` are tokens of their own, the source may follow them directly.
+pub(crate) fn sigspec_pair(input: Span) -> IResult<Span, (SigSpec, SigSpec)> {
+    let (input, dest) = sigspec(input)?;
+    let (input, _) = match dest {
+        SigSpec::Concat(_) | SigSpec::Range(..) => {
+            map(take_while(characters::is_sep), |_| ())(input)?
+        }
+        _ => characters::sep(input)?,
+    };
+    let (input, src) = sigspec(input)?;
+    Ok((input, (dest, src)))
 }
 
 /// `|  { <sigspec>* }`
@@ -122,6 +427,135 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_walk() {
+        let span = Span::new_extra("{ \\a [3:0] { 1'1 \\b } }", Default::default());
+        let sigspec = sigspec(span).unwrap().1;
+        let mut visited = vec![];
+        sigspec.walk(&mut |s| visited.push(s));
+        assert_eq!(visited.len(), 6);
+        assert_eq!(visited[0], &sigspec);
+        assert_eq!(visited[2], &SigSpec::WireId("a".to_string()));
+        assert_eq!(visited[5], &SigSpec::WireId("b".to_string()));
+    }
+
+    #[test]
+    fn test_replace_wire() {
+        let parse = |s| sigspec(Span::new_extra(s, Default::default())).unwrap().1;
+        let mut sigspec = parse("{ \\a [3:0] { 1'1 \\b { \\a } } \\ab }");
+        sigspec.replace_wire("a", "c");
+        assert_eq!(sigspec, parse("{ \\c [3:0] { 1'1 \\b { \\c } } \\ab }"));
+        sigspec.replace_wire("missing", "d");
+        assert_eq!(sigspec, parse("{ \\c [3:0] { 1'1 \\b { \\c } } \\ab }"));
+    }
+
+    #[test]
+    fn test_map_wires() {
+        let parse = |s| sigspec(Span::new_extra(s, Default::default())).unwrap().1;
+        let sigspec = parse("{ \\a [3:0] { 1'1 \\b { \\a } } $c }");
+        assert_eq!(
+            sigspec.map_wires(&mut |name| name.to_uppercase()),
+            parse("{ \\A [3:0] { 1'1 \\B { \\A } } $C }")
+        );
+        let mut seen = vec![];
+        parse("7").map_wires(&mut |name| {
+            seen.push(name.to_string());
+            name.to_string()
+        });
+        assert!(seen.is_empty());
+    }
+
+    #[test]
+    fn test_split_trailing_range() {
+        let parse = |s| sigspec(Span::new_extra(s, Default::default())).unwrap().1;
+        let mut sigspec = parse("\\a[3:0]");
+        assert_eq!(sigspec, SigSpec::wire("a[3:0]"));
+        sigspec.split_trailing_range();
+        assert_eq!(sigspec, parse("\\a [3:0]"));
+        let mut sigspec = parse("{ $0\\q[1] \\b[x] \\c[] \\d[1:0] [0] 1'1 }");
+        sigspec.split_trailing_range();
+        assert_eq!(
+            sigspec,
+            SigSpec::concat([
+                SigSpec::range("0\\q", 1, None),
+                SigSpec::wire("b[x]"),
+                SigSpec::wire("c[]"),
+                SigSpec::Range(Box::new(SigSpec::range("d", 1, Some(0))), 0, None),
+                SigSpec::value("1"),
+            ])
+        );
+        let mut sigspec = SigSpec::wire("[2]");
+        sigspec.split_trailing_range();
+        assert_eq!(sigspec, SigSpec::wire("[2]"));
+    }
+
+    #[test]
+    fn test_leaf_wires() {
+        let parse = |s| sigspec(Span::new_extra(s, Default::default())).unwrap().1;
+        let sigspec = parse("{ \\b [7:4] \\a [0] \\b [1:0] }");
+        assert_eq!(sigspec.leaf_wires(), ["b", "a"]);
+        assert!(parse("4'1010").leaf_wires().is_empty());
+        assert_eq!(parse("{ { \\a } \\a }").leaf_wires(), ["a"]);
+    }
+
+    #[test]
+    fn test_walk_order() {
+        let parse = |s| sigspec(Span::new_extra(s, Default::default())).unwrap().1;
+        let sigspec = parse("{ \\a [1] { \\b 1'0 } \\c }");
+        let mut kinds = vec![];
+        sigspec.walk(&mut |s| kinds.push(s.kind()));
+        use SigSpecKind::*;
+        assert_eq!(
+            kinds,
+            [Concat, Range, WireId, Concat, WireId, Constant, WireId]
+        );
+    }
+
+    #[test]
+    fn test_large_concat() {
+        let parts: Vec<SigSpec> = (0..4096)
+            .rev()
+            .map(|i| SigSpec::range("a", i, None))
+            .collect();
+        let concat = SigSpec::concat(parts);
+        let text = format!(
+            "{{ {} }}",
+            (0..4096)
+                .rev()
+                .map(|i| format!("\\a [{}]", i))
+                .collect::<Vec<_>>()
+                .join(" ")
+        );
+        assert_eq!(SigSpec::try_from(text.as_str()).unwrap(), concat);
+        let mut visited = 0;
+        concat.walk(&mut |_| visited += 1);
+        assert_eq!(visited, 1 + 2 * 4096);
+        assert_eq!(concat.leaf_wires(), ["a"]);
+
+        // nested deeper than the recursion of the parser would go
+        let mut nested = SigSpec::wire("a");
+        for _ in 0..10_000 {
+            nested = SigSpec::concat([nested]);
+        }
+        let mut visited = 0;
+        nested.walk(&mut |_| visited += 1);
+        assert_eq!(visited, 10_001);
+        // dropping is recursive, unwrap the nesting first
+        while let SigSpec::Concat(mut parts) = nested {
+            nested = parts.pop().unwrap();
+        }
+    }
+
+    #[test]
+    fn test_constructors() {
+        let parse = |s| sigspec(Span::new_extra(s, Default::default())).unwrap().1;
+        assert_eq!(SigSpec::wire("a"), parse("\\a"));
+        assert_eq!(SigSpec::range("a", 2, None), parse("\\a [2]"));
+        assert_eq!(SigSpec::value("10x"), parse("3'10x"));
+        assert_eq!(SigSpec::constant(Constant::Integer(5)), parse("5"));
+        assert_eq!(SigSpec::concat([]), parse("{ }"));
+    }
+
     #[test]
     fn test_sigspec_range() {
         let info: TracableInfo = TracableInfo::new().parser_width(64).fold("term");
@@ -136,6 +570,39 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_sigspec_range_negative() {
+        for input in ["\\a [-1]", "\\a [3:-1]", "\\a [-2:0]"] {
+            let e = crate::error::parse_complete(input, sigspec_range).unwrap_err();
+            assert_eq!(e.nom_kind(), Some(ErrorKind::Verify), "failed: {}", input);
+        }
+        let e = crate::parse("module \\m\n  wire \\a\n  connect \\a [-1] 1'0\nend\n").unwrap_err();
+        assert_eq!((*e.line(), *e.column()), (3, 15));
+    }
+
+    #[test]
+    fn test_sigspec_range_spacing() {
+        let vectors = [
+            ("\\a [3:0]", Some((3, Some(0)))),
+            ("\\a\t[3:0]", Some((3, Some(0)))),
+            ("\\a  [ 3 : 0 ]", Some((3, Some(0)))),
+            ("\\a [\t2\t]", Some((2, None))),
+            ("\\a[3:0]", None),
+        ];
+        for (input, expected) in vectors {
+            let span = Span::new_extra(input, Default::default());
+            let ret = sigspec_range(span)
+                .ok()
+                .map(|(_, (_, start, end))| (start, end));
+            assert_eq!(ret, expected, "failed: {}", input);
+        }
+        let span = Span::new_extra("\\a[3:0]", Default::default());
+        assert_eq!(
+            sigspec(span).unwrap().1,
+            SigSpec::WireId("a[3:0]".to_string())
+        );
+    }
+
     #[test]
     fn test_sigspec_concat() {
         let info: TracableInfo = TracableInfo::new().parser_width(64).fold("term");
@@ -0,0 +1,101 @@
+//! Source locations of `\src` attributes.
+//!
+//! Yosys records where an item comes from as `file:line.col-line.col`, ie.
+//! `serv_state.v:179.7-186.10`. A location may also be a single `file:line.col` or
+//! `file:line`, the missing parts defaulting to the start position and column 0.
+
+use crate::SrcLoc;
+
+impl SrcLoc {
+    /// Parse a single location, `None` if it is malformed
+    pub(crate) fn from_str(s: &str) -> Option<SrcLoc> {
+        // the file name may itself contain `:`
+        let (file, position) = s.rsplit_once(':')?;
+        if file.is_empty() {
+            return None;
+        }
+        let (start, end) = match position.split_once('-') {
+            Some((start, end)) => (position_from_str(start)?, position_from_str(end)?),
+            None => {
+                let start = position_from_str(position)?;
+                (start, start)
+            }
+        };
+        Some(SrcLoc {
+            file: file.to_string(),
+            start,
+            end,
+        })
+    }
+}
+
+/// `line.col` or `line`
+fn position_from_str(s: &str) -> Option<(usize, usize)> {
+    match s.split_once('.') {
+        Some((line, col)) => Some((line.parse().ok()?, col.parse().ok()?)),
+        None => Some((s.parse().ok()?, 0)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_parse_src() {
+        let src = Constant::String("serv_state.v:179.7-186.10".to_string());
+        assert_eq!(
+            parse_src(&src),
+            Some(SrcLoc {
+                file: "serv_state.v".to_string(),
+                start: (179, 7),
+                end: (186, 10),
+            })
+        );
+        let vectors = [
+            ("a.v:3", Some(("a.v", (3, 0), (3, 0)))),
+            ("a.v:3.4", Some(("a.v", (3, 4), (3, 4)))),
+            (
+                "C:\\rtl\\a.v:1.2-3.4",
+                Some(("C:\\rtl\\a.v", (1, 2), (3, 4))),
+            ),
+            ("a.v", None),
+            (":1.2-3.4", None),
+            ("a.v:x.2-3.4", None),
+        ];
+        for (input, expected) in vectors {
+            let expected = expected.map(|(file, start, end)| SrcLoc {
+                file: file.to_string(),
+                start,
+                end,
+            });
+            let src = Constant::String(input.to_string());
+            assert_eq!(parse_src(&src), expected, "failed: {}", input);
+        }
+        assert_eq!(parse_src(&Constant::Integer(1)), None);
+    }
+
+    #[test]
+    fn test_parse_srcs() {
+        let src = Constant::String("serv_alu.v:39.15-39.30|serv_top.v:100.1-120.3".to_string());
+        let locations = parse_srcs(&src);
+        assert_eq!(locations.len(), 2);
+        assert_eq!(locations[0].file(), "serv_alu.v");
+        assert_eq!(locations[1].file(), "serv_top.v");
+        assert_eq!(*locations[1].start(), (100, 1));
+        assert_eq!(*locations[1].end(), (120, 3));
+        assert_eq!(parse_src(&src).as_ref(), locations.first());
+    }
+
+    #[test]
+    fn test_src() {
+        let input = indoc::indoc! {r#"
+            attribute \src "serv_state.v:179.7-186.10"
+            wire \a
+        "#};
+        let span = Span::new_extra(input, Default::default());
+        let (_, (_, wire)) = wire::wire(span).unwrap();
+        assert_eq!(*wire.src().unwrap().start(), (179, 7));
+    }
+}
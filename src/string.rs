@@ -9,7 +9,7 @@
 //! Comments
 //! A comment starts with a # character and proceeds to the end of the line. All comments are ignored.
 
-use crate::Span;
+use crate::{IResult, Span};
 use nom::{
     branch::alt,
     bytes::complete::{is_not, tag, take_while, take_while_m_n},
@@ -18,7 +18,7 @@ use nom::{
     error::{ErrorKind, FromExternalError, ParseError},
     multi::fold_many0,
     sequence::{delimited, preceded},
-    AsChar, IResult, Parser,
+    AsChar, Parser,
 };
 use nom_tracable::tracable_parser;
 
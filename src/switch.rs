@@ -12,19 +12,100 @@
 //! <case-body>         ::= (<switch> | <assign-stmt>)*
 //! <switch-end-stmt>   ::= end <eol>
 //! ```
+//!
+//! In lenient mode, a case body may hold `connect` statements, read as assignments, see
+//! `ParseOptions::lenient`.
 
 use crate::*;
 use nom::{
     branch::alt,
     bytes::complete::tag,
-    combinator::{map, opt},
+    combinator::{map, opt, peek, verify},
     multi::many0,
-    sequence::separated_pair,
-    IResult,
+    sequence::{delimited, preceded},
 };
 use nom_tracable::tracable_parser;
 use std::collections::HashMap;
 
+impl Switch {
+    /// The nesting depth of this switch, 1 when no case holds another switch
+    pub fn depth(&self) -> usize {
+        1 + self
+            .nested()
+            .map(|switch| switch.depth())
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// The first case taken when the switch signal holds `value`, bits least significant first
+    ///
+    /// A case matches when `value` equals any of its comma-separated patterns, as in Yosys: `-`
+    /// bits of a pattern match any bit, other bits must be the same. A case without pattern is
+    /// the default and always matches. Patterns that are not constant values, or are not as wide
+    /// as `value`, never match.
+    ///
+    /// ```
+    /// let src = "module \\m\n  process $p\n    switch 2'10\n      case 2'0-, 2'1-\n    \
+    ///            end\n  end\nend\n";
+    /// let design = rtlicious::parse(src).unwrap();
+    /// let switch = &design.modules()["m"].processes()["p"].switches()[0];
+    /// let value = switch.switch_on_sigspec().constant_value().unwrap();
+    /// assert_eq!(switch.select_case(value), Some(&switch.cases()[0]));
+    /// ```
+    pub fn select_case(&self, value: &[char]) -> Option<&Case> {
+        self.cases.iter().find(|case| match &case.compare_against {
+            None => true,
+            Some(patterns) => patterns.iter().any(|pattern| {
+                let SigSpec::Constant(constant) = pattern else {
+                    return false;
+                };
+                match &*constant.expanded() {
+                    Constant::Value(bits) => {
+                        bits.len() == value.len()
+                            && bits
+                                .iter()
+                                .zip(value)
+                                .all(|(bit, value)| *bit == '-' || bit == value)
+                    }
+                    _ => false,
+                }
+            }),
+        })
+    }
+
+    /// The number of cases of this switch and of the switches nested in it
+    pub(crate) fn case_count(&self) -> usize {
+        self.cases.len()
+            + self
+                .nested()
+                .map(|switch| switch.case_count())
+                .sum::<usize>()
+    }
+
+    /// The number of assignments in the cases of this switch and of the switches nested in it
+    pub(crate) fn assign_count(&self) -> usize {
+        self.bodies()
+            .map(|body| match body {
+                CaseBody::Assign(_) => 1,
+                CaseBody::Switch(switch) => switch.assign_count(),
+            })
+            .sum()
+    }
+
+    /// The bodies of every case of this switch
+    fn bodies(&self) -> impl Iterator<Item = &CaseBody> {
+        self.cases.iter().flat_map(|case| &case.case_bodies)
+    }
+
+    /// The switches directly nested in the cases of this switch
+    fn nested(&self) -> impl Iterator<Item = &Switch> {
+        self.bodies().filter_map(|body| match body {
+            CaseBody::Switch(switch) => Some(switch),
+            CaseBody::Assign(_) => None,
+        })
+    }
+}
+
 #[tracable_parser]
 pub(crate) fn switch(input: Span) -> IResult<Span, Switch> {
     let (input, attributes_and_against) = switch_stmt(input)?;
@@ -84,7 +165,8 @@ pub(crate) fn compare(input: Span) -> IResult<Span, Vec<SigSpec>> {
     // many0, sigpspec, preceded by a comma
     let (input, first) = crate::sigspec::sigspec(input)?;
     let (input, others) = many0(|input| {
-        let (input, _) = separated_pair(characters::sep, tag(","), characters::sep)(input)?;
+        // the Yosys lexer reads a comma as a token of its own, spaces around it are optional
+        let (input, _) = delimited(opt(characters::sep), tag(","), opt(characters::sep))(input)?;
         crate::sigspec::sigspec(input)
     })(input)?;
 
@@ -105,15 +187,44 @@ pub(crate) fn case_body(input: Span) -> IResult<Span, Vec<crate::switch::CaseBod
     many0(alt((
         map(crate::switch::switch, CaseBody::Switch),
         map(process::assign_stmt, CaseBody::Assign),
+        map(case_connect_stmt, CaseBody::Assign),
     )))(input)
 }
 
+/// `connect <sigspec> <sigspec> <eol>` in a case body, read as an assignment
+///
+/// Only accepted in lenient mode.
+fn case_connect_stmt(input: Span) -> IResult<Span, (SigSpec, SigSpec)> {
+    let (rest, connection) = preceded(
+        verify(peek(tag("connect")), |_| ParseOptions::lenient()),
+        connect::conn_stmt,
+    )(input)?;
+    ParseOptions::report(input, "connect statement in a case, read as an assignment");
+    Ok((rest, connection))
+}
+
 #[cfg(test)]
 mod tests {
 
     use super::*;
     use indoc::indoc;
     use pretty_assertions::assert_eq;
+
+    /// A switch nested in the default case of another switch
+    const NESTED: &str = indoc! {r#"
+            attribute \src "serv_state.v:179.7-186.10"
+            switch 1'0
+              case 
+                attribute \src "serv_state.v:183.16-186.10"
+                switch 1'1
+                  case 1'1
+                    assign $flatten\state.$0\o_cnt[2:0] $flatten\state.$add$serv_state.v:184$936_Y
+                    assign $flatten\state.$0\cnt_r[3:0] { \state.cnt_r [2:0] $flatten\state.$or$serv_state.v:185$941_Y }
+                  case 
+                end
+            end
+            "#};
+
     #[test]
     fn test_switch() {
         let input = indoc! {r#"
@@ -200,6 +311,32 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_case_connect() {
+        let input = indoc! {r#"
+            switch \s
+              case 1'1
+                assign \a 1'0
+                connect \b \a
+            end
+        "#};
+        let span = || Span::new_extra(input, Default::default());
+        assert!(switch(span()).is_err());
+        let lenient = ParseOptions {
+            lenient: true,
+            ..Default::default()
+        };
+        let (rest, switch) = lenient.scope(|| switch(span())).unwrap();
+        assert!(rest.is_empty());
+        assert_eq!(
+            switch.cases[0].case_bodies,
+            vec![
+                CaseBody::Assign((SigSpec::wire("a"), SigSpec::value("0"))),
+                CaseBody::Assign((SigSpec::wire("b"), SigSpec::wire("a"))),
+            ]
+        );
+    }
+
     #[test]
     fn test_switch_stmt() {
         let vectors = vec![
@@ -269,6 +406,18 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_compare_comma_without_space() {
+        let span = Span::new_extra("1'1, 1'0", Default::default());
+        assert_eq!(
+            compare(span).unwrap().1,
+            vec![
+                SigSpec::Constant(Constant::Value(vec!['1'])),
+                SigSpec::Constant(Constant::Value(vec!['0'])),
+            ]
+        );
+    }
+
     #[test]
     fn test_switch_end_stmt() {
         let vectors = vec![("end\n", "")];
@@ -278,4 +427,79 @@ mod tests {
             assert_eq!(ret.1, input.1);
         }
     }
+
+    #[test]
+    fn test_select_case() {
+        let input = indoc! {r#"
+            switch \s
+              case 1'1, 1'0
+                assign \a 1'1
+              case 1'x
+                assign \a 1'0
+            end
+        "#};
+        let span = Span::new_extra(input, Default::default());
+        let mut switch = switch(span).unwrap().1;
+        let first = Some(&switch.cases[0]);
+        assert_eq!(switch.select_case(&['1']), first);
+        assert_eq!(switch.select_case(&['0']), first);
+        assert_eq!(switch.select_case(&['x']), Some(&switch.cases[1]));
+        assert_eq!(switch.select_case(&['1', '1']), None);
+        // the default case matches what the others do not
+        switch.cases[1].compare_against = None;
+        assert_eq!(switch.select_case(&['1', '1']), Some(&switch.cases[1]));
+    }
+
+    #[test]
+    fn test_select_case_dont_care() {
+        let input = indoc! {r#"
+            switch \s
+              case 2'1-
+                assign \a 1'1
+              case \b
+                assign \a 1'0
+            end
+        "#};
+        let span = Span::new_extra(input, Default::default());
+        let switch = switch(span).unwrap().1;
+        // 2'1- holds '-' then '1', least significant first
+        assert_eq!(switch.select_case(&['0', '1']), Some(&switch.cases[0]));
+        assert_eq!(switch.select_case(&['1', '1']), Some(&switch.cases[0]));
+        assert_eq!(switch.select_case(&['1', '0']), None);
+    }
+
+    #[test]
+    fn test_switch_depth() {
+        let span = Span::new_extra(NESTED, Default::default());
+        let switch = switch(span).unwrap().1;
+        assert_eq!(switch.depth(), 2);
+        assert_eq!(switch.case_count(), 3);
+        assert_eq!(switch.assign_count(), 2);
+        let CaseBody::Switch(inner) = &switch.cases[0].case_bodies[0] else {
+            panic!("expected a nested switch");
+        };
+        assert_eq!(inner.depth(), 1);
+    }
+
+    #[test]
+    fn test_switch_attributes_round_trip() {
+        let input = format!(
+            "module \\m\n  process $p\n{}  end\nend\n",
+            NESTED.replace("case 1'1\n", "attribute \\full_case 1\ncase 1'1\n")
+        );
+        let design = crate::parse(&input).unwrap();
+        let written = design.to_rtlil_string();
+        let lines: Vec<&str> = written.lines().map(str::trim).collect();
+        let after = |line: &str| lines[lines.iter().position(|l| *l == line).unwrap() + 1];
+        assert_eq!(
+            after(r#"attribute \src "serv_state.v:179.7-186.10""#),
+            "switch 1'0"
+        );
+        assert_eq!(
+            after(r#"attribute \src "serv_state.v:183.16-186.10""#),
+            "switch 1'1"
+        );
+        assert_eq!(after(r"attribute \full_case 1"), "case 1'1");
+        assert_eq!(crate::parse(&written).unwrap(), design);
+    }
 }
@@ -5,7 +5,7 @@
 //!     Initialization
 //!     Always
 //! ```text
-//! <sync>          ::= <sync-stmt> <update-stmt>*
+//! <sync>          ::= <sync-stmt> (<attr-stmt>* <update-stmt>)*
 //! <sync-stmt>     ::= sync <sync-type> <sigspec> <eol>
 //!                  |  sync global <eol>
 //!                  |  sync init <eol>
@@ -15,15 +15,57 @@
 //! ```
 
 use crate::*;
-use nom::{branch::alt, bytes::complete::tag, combinator::map, multi::many0, IResult};
+use nom::{branch::alt, bytes::complete::tag, combinator::map, multi::many0, sequence::pair};
 use nom_tracable::tracable_parser;
 
-/// `<sync> ::= <sync-stmt> <update-stmt>*`
+impl Sync {
+    /// The updates as `(dest, src)` pairs, without their attributes
+    pub(crate) fn update_pairs(&self) -> Vec<(SigSpec, SigSpec)> {
+        self.updates
+            .iter()
+            .map(|update| (update.dest.clone(), update.src.clone()))
+            .collect()
+    }
+}
+
+impl Memwr {
+    /// The write ports this one has priority over, the set bits of a constant priority mask
+    ///
+    /// Bit `i` of the mask is set when this write wins over the write port `i` of the memory.
+    /// Only `1` bits are set, integer masks are 32 bits wide. `None` if the mask is not a constant
+    /// bit pattern.
+    pub fn priority_over(&self) -> Option<Vec<usize>> {
+        let SigSpec::Constant(mask) = &self.priority_mask else {
+            return None;
+        };
+        let width = match mask {
+            Constant::Value(bits) => bits.len(),
+            Constant::Fill(_, width) => *width,
+            Constant::Integer(_) => 32,
+            _ => return None,
+        };
+        Some(
+            (0..width)
+                .filter(|i| mask.bit_at(*i) == Some('1'))
+                .collect(),
+        )
+    }
+}
+
+/// `<sync> ::= <sync-stmt> (<attr-stmt>* <update-stmt>)*`
 #[tracable_parser]
 pub(crate) fn sync(input: Span) -> IResult<Span, Sync> {
     let (input, sync_event) = sync_stmt(input)?;
-    let (input, updates) = many0(update_stmt)(input)?;
+    let (input, attributed_updates) = many0(pair(many0(attribute::attr_stmt), update_stmt))(input)?;
     let (input, memwrs) = many0(memwr_stmt)(input)?;
+    let updates = attributed_updates
+        .into_iter()
+        .map(|(attributes, (dest, src))| Update {
+            attributes: attributes.into_iter().collect(),
+            dest,
+            src,
+        })
+        .collect();
     Ok((
         input,
         Sync {
@@ -79,9 +121,7 @@ pub(crate) fn sync_type(input: Span) -> IResult<Span, SignalSync> {
 pub(crate) fn update_stmt(input: Span) -> IResult<Span, (SigSpec, SigSpec)> {
     let (input, _) = tag("update")(input)?;
     let (input, _) = characters::sep(input)?;
-    let (input, dest) = crate::sigspec::sigspec(input)?;
-    let (input, _) = characters::sep(input)?;
-    let (input, src) = crate::sigspec::sigspec(input)?;
+    let (input, (dest, src)) = crate::sigspec::sigspec_pair(input)?;
     let (input, _) = characters::eol(input)?;
     Ok((input, (dest, src)))
 }
@@ -133,14 +173,16 @@ mod tests {
             Sync {
                 sync_event: SyncOn::Global,
                 updates: vec![
-                    (
-                        SigSpec::WireId("a".to_string()),
-                        SigSpec::WireId("b".to_string()),
-                    ),
-                    (
-                        SigSpec::WireId("c".to_string()),
-                        SigSpec::WireId("d".to_string()),
-                    ),
+                    Update {
+                        attributes: HashMap::new(),
+                        dest: SigSpec::WireId("a".to_string()),
+                        src: SigSpec::WireId("b".to_string()),
+                    },
+                    Update {
+                        attributes: HashMap::new(),
+                        dest: SigSpec::WireId("c".to_string()),
+                        src: SigSpec::WireId("d".to_string()),
+                    },
                 ],
                 memwrs: HashMap::new(),
             },
@@ -152,6 +194,49 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_sync_update_attributes() {
+        let input = indoc! {r#"
+            sync posedge \clk
+            update \a \b
+            attribute \src "top.v:3.5-3.20"
+            attribute \keep 1
+            update \c \d
+            attribute \src "top.v:4.5-4.20"
+            memwr \mem \addr \data \en 0
+        "#};
+        let span = Span::new_extra(input, Default::default());
+        let (rem, sync) = sync(span).unwrap();
+        assert!(rem.is_empty());
+        assert_eq!(sync.updates.len(), 2);
+        assert!(sync.updates[0].attributes.is_empty());
+        let attributes = &sync.updates[1].attributes;
+        assert_eq!(attributes["keep"], Constant::Integer(1));
+        assert_eq!(
+            attributes["src"],
+            Constant::String("top.v:3.5-3.20".to_string())
+        );
+        assert_eq!(sync.memwrs["mem"].attributes.len(), 1);
+    }
+
+    #[test]
+    fn test_priority_over() {
+        let input = indoc! {r#"
+            sync posedge \clk
+            memwr \a \addr \data \en 4'1010
+            memwr \b \addr \data \en 6
+            memwr \c \addr \data \en 2'x1
+            memwr \d \addr \data \en \mask
+        "#};
+        let span = Span::new_extra(input, Default::default());
+        let (_, sync) = sync(span).unwrap();
+        let priority = |name: &str| sync.memwrs[name].priority_over();
+        assert_eq!(priority("a"), Some(vec![1, 3]));
+        assert_eq!(priority("b"), Some(vec![1, 2]));
+        assert_eq!(priority("c"), Some(vec![0]));
+        assert_eq!(priority("d"), None);
+    }
+
     #[test]
     fn test_sync_stmt() {
         let vectors = vec![
@@ -202,6 +287,18 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_update_stmt_concat_dest() {
+        let expected = (
+            SigSpec::concat([SigSpec::wire("a"), SigSpec::wire("b")]),
+            SigSpec::wire("c"),
+        );
+        for input in ["update { \\a \\b } \\c\n", "update { \\a \\b }\\c\n"] {
+            let span = Span::new_extra(input, Default::default());
+            assert_eq!(update_stmt(span).unwrap().1, expected, "failed: {}", input);
+        }
+    }
+
     #[test]
     fn test_memwr_stmt() {
         let vectors = vec![(
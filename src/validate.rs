@@ -0,0 +1,369 @@
+//! Consistency checks of a parsed module, beyond what the grammar enforces.
+
+use std::collections::BTreeSet;
+
+use crate::*;
+
+impl Module {
+    /// Check that the module is consistent, listing every problem found
+    ///
+    /// Signals may only refer to wires the module declares, anywhere in the module, even after
+    /// the signal: the wires are looked up once the whole module is read. Both sides of a module
+    /// connection must be as wide, as must a wire and the value of its `\init` attribute when it
+    /// is given as bits, and ranges may only select bits of their wire. Unknown wires come first,
+    /// sorted, then ranges beyond their wire by wire name and index, then width mismatches in the
+    /// order of the connections, then initial values by wire name. Blackboxes are not checked.
+    ///
+    /// In a module parsed with `ParseOptions::partial`, unknown wires may be declared in what was
+    /// not dumped: they are left out here, the parse reports them as diagnostics instead.
+    ///
+    /// ```
+    /// let src = "module \\m\n  wire width 2 \\a\n  connect \\a \\b\nend\n";
+    /// let design = rtlicious::parse(src).unwrap();
+    /// let problems = design.modules()["m"].validate().unwrap_err();
+    /// assert_eq!(problems, vec![rtlicious::ValidationError::UnknownWire("b".to_string())]);
+    /// ```
+    pub fn validate(&self) -> Result<(), Vec<ValidationError>> {
+        if self.is_blackbox() {
+            return Ok(());
+        }
+        let unknown = if self.partial {
+            BTreeSet::new()
+        } else {
+            self.unknown_wires()
+        };
+        let mut problems: Vec<ValidationError> = unknown
+            .into_iter()
+            .map(ValidationError::UnknownWire)
+            .collect();
+        let mut beyond = BTreeSet::new();
+        for signal in self.signals() {
+            signal.walk(&mut |sigspec| {
+                if let SigSpec::Range(inner, start, end) = sigspec {
+                    let SigSpec::WireId(name) = inner.as_ref() else {
+                        return;
+                    };
+                    let index = *start.max(&end.unwrap_or(*start));
+                    if self.wire(name).is_some_and(|wire| index >= wire.width) {
+                        beyond.insert((name.clone(), index));
+                    }
+                }
+            });
+        }
+        problems.extend(
+            beyond
+                .into_iter()
+                .map(|(wire, index)| ValidationError::RangeOutOfBounds { wire, index }),
+        );
+        for (index, (_, _, dest, src)) in self.connection_pairs().into_iter().enumerate() {
+            // an unknown wire or a range beyond its wire has no width, both are already reported
+            if let (Some(dest), Some(src)) = (dest, src) {
+                if dest != src {
+                    problems.push(ValidationError::WidthMismatch { index, dest, src });
+                }
+            }
+        }
+        let mut inits: Vec<(&str, &Wire, usize)> = self
+            .wires
+            .iter()
+            .filter_map(|(id, wire)| {
+                let init = match wire.init_value()? {
+                    Constant::Value(bits) => bits.len(),
+                    Constant::Fill(_, width) => *width,
+                    _ => return None,
+                };
+                (init != wire.width).then_some((id.inner().as_str(), wire, init))
+            })
+            .collect();
+        inits.sort_by_key(|(name, _, _)| *name);
+        problems.extend(inits.into_iter().map(|(name, wire, init)| {
+            ValidationError::InitWidthMismatch {
+                wire: name.to_string(),
+                width: wire.width,
+                init,
+            }
+        }));
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(problems)
+        }
+    }
+
+    /// The names of the wires the signals refer to that the module does not declare, sorted
+    pub(crate) fn unknown_wires(&self) -> BTreeSet<String> {
+        let mut unknown = BTreeSet::new();
+        for signal in self.signals() {
+            for name in signal.leaf_wires() {
+                if self.wire(name).is_none() {
+                    unknown.insert(name.to_string());
+                }
+            }
+        }
+        unknown
+    }
+
+    /// Every signal of the module: in connections, cells and processes
+    pub(crate) fn signals(&self) -> Vec<&SigSpec> {
+        let mut signals: Vec<&SigSpec> = self
+            .connections
+            .iter()
+            .flat_map(|(dest, src)| [dest, src])
+            .collect();
+        for cell in self.cells.values() {
+            signals.extend(cell.connections.values());
+        }
+        for process in self.processes.values() {
+            signals.extend(
+                process
+                    .assignments
+                    .iter()
+                    .flat_map(|(dest, src)| [dest, src]),
+            );
+            let mut switches: Vec<&Switch> = process.switches.iter().collect();
+            while let Some(switch) = switches.pop() {
+                signals.push(&switch.switch_on_sigspec);
+                for case in &switch.cases {
+                    signals.extend(case.compare_against.iter().flatten());
+                    for body in &case.case_bodies {
+                        match body {
+                            CaseBody::Assign((dest, src)) => signals.extend([dest, src]),
+                            CaseBody::Switch(switch) => switches.push(switch),
+                        }
+                    }
+                }
+            }
+            for sync in &process.syncs {
+                if let SyncOn::Signal(_, signal) = &sync.sync_event {
+                    signals.push(signal);
+                }
+                signals.extend(sync.updates.iter().flat_map(|u| [&u.dest, &u.src]));
+                for memwr in sync.memwrs.values() {
+                    signals.extend([
+                        &memwr.address,
+                        &memwr.data,
+                        &memwr.enable,
+                        &memwr.priority_mask,
+                    ]);
+                }
+            }
+        }
+        signals
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use crate::*;
+    use indoc::indoc;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_validate() {
+        let input = indoc! {r#"
+            module \m
+              wire width 2 \a
+              wire \b
+              connect \a \b
+              connect \a { \b \b }
+              cell $not $n
+                connect \A \c
+                connect \Y \b
+              end
+              process $p
+                switch \d
+                  case 1'1
+                    assign \b \e [0]
+                end
+                sync posedge \clk
+                  update \b \a [1]
+              end
+            end
+        "#};
+        let design = parse(input).unwrap();
+        assert_eq!(
+            design.modules()["m"].validate(),
+            Err(vec![
+                ValidationError::UnknownWire("c".to_string()),
+                ValidationError::UnknownWire("clk".to_string()),
+                ValidationError::UnknownWire("d".to_string()),
+                ValidationError::UnknownWire("e".to_string()),
+                ValidationError::WidthMismatch {
+                    index: 0,
+                    dest: 2,
+                    src: 1
+                },
+            ])
+        );
+    }
+
+    #[test]
+    fn test_validate_init() {
+        let input = indoc! {r#"
+            module \m
+              attribute \init 4'0101
+              wire width 4 \q
+              attribute \init 2'01
+              wire width 4 \r
+            end
+        "#};
+        let design = parse(input).unwrap();
+        let module = &design.modules()["m"];
+        assert_eq!(
+            module.wire("q").unwrap().init_value(),
+            Some(&Constant::Value(vec!['1', '0', '1', '0']))
+        );
+        assert_eq!(
+            module.validate(),
+            Err(vec![ValidationError::InitWidthMismatch {
+                wire: "r".to_string(),
+                width: 4,
+                init: 2
+            }])
+        );
+        assert_eq!(
+            module.validate().unwrap_err()[0].to_string(),
+            "wire r is 4 bits wide but its initial value has 2 bits"
+        );
+    }
+
+    #[test]
+    fn test_validate_leading_connect() {
+        // wires are resolved once the whole module is read, not as it is read
+        let design = parse(include_str!("../tests/fixtures/leading_connect.il")).unwrap();
+        let module = &design.modules()["leading_connect"];
+        assert_eq!(
+            module.connections()[0],
+            (SigSpec::wire("y"), SigSpec::wire("not_y"))
+        );
+        assert_eq!(module.wires().len(), 3);
+        assert_eq!(module.validate(), Ok(()));
+    }
+
+    #[test]
+    fn test_validate_partial() {
+        let input = indoc! {r#"
+            module \m
+              wire width 2 \a
+              connect \a \b
+              connect \a 1'0
+            end
+        "#};
+        let partial = ParseOptions {
+            partial: true,
+            ..Default::default()
+        };
+        let design = parse_with(input, &partial).unwrap();
+        let module = &design.modules()["m"];
+        assert!(module.partial());
+        // the unknown wire is only left out, other problems are still reported
+        assert_eq!(
+            module.validate(),
+            Err(vec![ValidationError::WidthMismatch {
+                index: 1,
+                dest: 2,
+                src: 1
+            }])
+        );
+        let design = parse(input).unwrap();
+        assert!(!design.modules()["m"].partial());
+        assert_eq!(
+            design.modules()["m"].validate().unwrap_err()[0],
+            ValidationError::UnknownWire("b".to_string())
+        );
+    }
+
+    #[test]
+    fn test_validate_partial_diagnostics() {
+        let input = indoc! {r#"
+            module \m
+              wire width 2 \a
+              connect \a { \c \b }
+            end
+        "#};
+        let collected = Arc::new(Mutex::new(vec![]));
+        let sink = collected.clone();
+        let partial = ParseOptions {
+            partial: true,
+            diagnostics: Some(DiagnosticSink::new(move |diagnostic| {
+                sink.lock().unwrap().push(diagnostic)
+            })),
+            ..Default::default()
+        };
+        parse_with(input, &partial).unwrap();
+        let diagnostics: Vec<String> = collected
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|diagnostic| diagnostic.to_string())
+            .collect();
+        assert_eq!(
+            diagnostics,
+            [
+                "line 1, column 1: no wire named b in the partial module",
+                "line 1, column 1: no wire named c in the partial module",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_validate_range_out_of_bounds() {
+        let input = indoc! {r#"
+            module \m
+              wire \a
+              wire \b
+              wire width 4 \c
+              connect \a [5:0] \b
+              connect \c [3] \a [7]
+              connect \c [3:0] 4'0000
+            end
+        "#};
+        let design = parse(input).unwrap();
+        let problems = design.modules()["m"].validate().unwrap_err();
+        assert_eq!(
+            problems,
+            vec![
+                ValidationError::RangeOutOfBounds {
+                    wire: "a".to_string(),
+                    index: 5
+                },
+                ValidationError::RangeOutOfBounds {
+                    wire: "a".to_string(),
+                    index: 7
+                },
+            ]
+        );
+        assert_eq!(problems[0].to_string(), "bit 5 is beyond the end of wire a");
+    }
+
+    #[test]
+    fn test_validate_empty() {
+        let design = parse("module \\a\nend\n").unwrap();
+        let module = &design.modules()["a"];
+        assert_eq!(module.items().count(), 0);
+        assert_eq!(module.validate(), Ok(()));
+    }
+
+    #[test]
+    fn test_validate_connections_only() {
+        let input = indoc! {r#"
+            module \a
+              connect \x \y
+              connect { \x \y } 2'10
+            end
+        "#};
+        let design = parse(input).unwrap();
+        let module = &design.modules()["a"];
+        assert!(module.wires().is_empty());
+        assert_eq!(module.connections().len(), 2);
+        assert_eq!(
+            module.validate(),
+            Err(vec![
+                ValidationError::UnknownWire("x".to_string()),
+                ValidationError::UnknownWire("y".to_string()),
+            ])
+        );
+    }
+}
@@ -9,12 +9,13 @@
 use nom::{
     bytes::complete::tag,
     character::complete::one_of,
-    combinator::opt,
+    combinator::{opt, recognize},
+    error::{ErrorKind, ParseError},
     multi::{many0, many1},
-    IResult,
+    sequence::tuple,
 };
 
-use crate::Span;
+use crate::{Constant, IResult, NomError, ParseOptions, Span};
 
 /// `<decimal-digit> ::= 0 | 1 | 2 | 3 | 4 | 5 | 6 | 7 | 8 | 9`
 fn decimal_digit(input: Span) -> IResult<Span, char> {
@@ -22,41 +23,110 @@ fn decimal_digit(input: Span) -> IResult<Span, char> {
 }
 
 /// `<binary-digit>  ::= 0 | 1 | x | z | m | -`
+///
+/// Uppercase `X`, `Z` and `M` are accepted and normalized to lowercase, so that equal values
+/// compare and hash equal. The writer emits them lowercase as well.
 fn binary_digit(input: Span) -> IResult<Span, char> {
-    one_of("01xzXZmM-")(input)
+    let (input, digit) = one_of("01xzXZmM-")(input)?;
+    Ok((input, digit.to_ascii_lowercase()))
+}
+
+/// <real>          ::= -? <decimal-digit>+ . <decimal-digit>+ ((e | E) (+ | -)? <decimal-digit>+)?
+///
+/// The fraction is required, it tells a real from an integer.
+pub(crate) fn real(input: Span) -> IResult<Span, String> {
+    let (input, real) = recognize(tuple((
+        opt(tag("-")),
+        many1(decimal_digit),
+        tag("."),
+        many1(decimal_digit),
+        opt(tuple((
+            one_of("eE"),
+            opt(one_of("+-")),
+            many1(decimal_digit),
+        ))),
+    )))(input)?;
+    Ok((input, real.fragment().to_string()))
 }
 
 /// <integer>       ::= -? <decimal-digit>+
+///
+/// An integer that does not fit in an `i32` is a failure of kind `TooLarge`.
 pub(crate) fn integer(input: Span) -> IResult<Span, i32> {
+    let start = input;
     let (input, sign) = opt(tag("-"))(input)?;
     // use decimal_digit
     let (input, digits) = many1(decimal_digit)(input)?;
-    // parse the digits as a string and then parse the string as an i64
-    let digits: String = digits.into_iter().collect();
-    let integer = digits.parse::<i32>().unwrap();
-    // if the sign is present, negate the integer
-    let integer = if sign.is_some() { -integer } else { integer };
+    // parse the sign and digits as a string, so that i32::MIN does not overflow
+    let digits: String = sign.into_iter().map(|_| '-').chain(digits).collect();
+    let Ok(integer) = digits.parse::<i32>() else {
+        return Err(nom::Err::Failure(NomError::from_error_kind(
+            start,
+            ErrorKind::TooLarge,
+        )));
+    };
     Ok((input, integer))
 }
 
 /// <value>         ::= <decimal-digit>+ ' <binary-digit>*
-pub(crate) fn value(input: Span) -> IResult<Span, Vec<char>> {
+///
+/// A single bit is repeated to the declared width, any other width mismatch is a failure of
+/// kind `Verify`. A width beyond `ParseOptions::max_value_width` is a failure of kind `TooLarge`.
+/// A missing width, as in `'101`, or one that is not decimal, as in `1e3'0`, is a failure of kind
+/// `Digit` at the offending character.
+///
+/// With `ParseOptions::exact_values`, a single bit for a wider width is kept as a
+/// `Constant::Fill` rather than repeated.
+pub(crate) fn value(input: Span) -> IResult<Span, Constant> {
+    let start = input;
+    if input.fragment().starts_with('\'') {
+        return Err(nom::Err::Failure(NomError::from_error_kind(
+            input,
+            ErrorKind::Digit,
+        )));
+    }
     let (input, digits) = many1(decimal_digit)(input)?;
+    // digits then a letter are no integer nor real, a `'` further in the word makes it a value
+    let word = input
+        .fragment()
+        .split(|c: char| c.is_ascii_whitespace())
+        .next();
+    if input
+        .fragment()
+        .starts_with(|c: char| c.is_ascii_alphabetic())
+        && word.is_some_and(|word| word.contains('\''))
+    {
+        return Err(nom::Err::Failure(NomError::from_error_kind(
+            input,
+            ErrorKind::Digit,
+        )));
+    }
     let (input, _) = tag("'")(input)?;
+    let parsed_size = match digits.iter().collect::<String>().parse::<usize>() {
+        Ok(size) if size <= ParseOptions::max_value_width() => size,
+        _ => {
+            return Err(nom::Err::Failure(NomError::from_error_kind(
+                start,
+                ErrorKind::TooLarge,
+            )))
+        }
+    };
     let (input, binary_digits) = many0(binary_digit)(input)?;
-    let parsed_size = digits.iter().collect::<String>().parse::<i64>().unwrap();
-    if parsed_size != binary_digits.len() as i64 {
-        // TODO: will assume that when there only 1 digi, extend. Otherwise, panic
-        if binary_digits.len() == 1 {
-            let binary_digits = vec![binary_digits[0]; parsed_size as usize];
-            return Ok((input, binary_digits));
-        } else {
-            dbg!(parsed_size, binary_digits);
-            unimplemented!("Size of value does not match the number of bits");
+    // a single bit is extended to the declared width
+    if parsed_size != binary_digits.len() && binary_digits.len() != 1 {
+        return Err(nom::Err::Failure(NomError::from_error_kind(
+            start,
+            ErrorKind::Verify,
+        )));
+    }
+    if binary_digits.len() == 1 && parsed_size != 1 {
+        if ParseOptions::exact_values() {
+            return Ok((input, Constant::Fill(binary_digits[0], parsed_size)));
         }
+        return Ok((input, Constant::Value(vec![binary_digits[0]; parsed_size])));
     }
     let binary_digits: Vec<char> = binary_digits.into_iter().rev().collect();
-    Ok((input, binary_digits))
+    Ok((input, Constant::Value(binary_digits)))
 }
 
 #[cfg(test)]
@@ -98,11 +168,11 @@ mod tests {
             ("0", '0'),
             ("1", '1'),
             ("x", 'x'),
-            ("X", 'X'),
+            ("X", 'x'),
             ("z", 'z'),
-            ("Z", 'Z'),
+            ("Z", 'z'),
             ("m", 'm'),
-            ("M", 'M'),
+            ("M", 'm'),
             ("-", '-'),
         ];
         let info = TracableInfo::new().parser_width(64).fold("term");
@@ -162,6 +232,31 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_integer_bounds() {
+        // the sign is parsed with the digits, the most negative integer does not overflow
+        for (input, expected) in [("2147483647", i32::MAX), ("-2147483648", i32::MIN)] {
+            let span = Span::new_extra(input, Default::default());
+            assert_eq!(integer(span).unwrap().1, expected);
+        }
+    }
+
+    #[test]
+    fn test_integer_too_large() {
+        for input in ["2147483648", "-2147483649", "99999999999"] {
+            let e = crate::error::parse_complete(input, integer).unwrap_err();
+            assert_eq!(e.nom_kind(), Some(ErrorKind::TooLarge));
+        }
+        let e = crate::parse("attribute \\x 99999999999\nmodule \\a\nend\n").unwrap_err();
+        assert_eq!((*e.line(), *e.column()), (1, 14));
+        for input in [
+            "module \\a\n  wire width 99999999999 \\w\nend\n",
+            "module \\a\n  wire \\b\n  connect \\b [99999999999] 1'0\nend\n",
+        ] {
+            assert!(crate::parse(input).is_err());
+        }
+    }
+
     #[test]
     fn test_value() {
         let vectors = [
@@ -180,15 +275,62 @@ mod tests {
         for (i, (input, expected)) in vectors.iter().enumerate() {
             let span = LocatedSpan::new_extra(*input, info);
             let ret = value(span).unwrap();
-            assert_eq!(ret.1, *expected, "Test case {}", i);
+            assert_eq!(ret.1, Constant::Value(expected.clone()), "Test case {}", i);
         }
     }
-    // should fail if the number of bits does not match the number of bits
     #[test]
-    #[should_panic]
-    fn test_value_panic() {
+    fn test_value_case_insensitive() {
         let info = TracableInfo::new().parser_width(64).fold("term");
-        let span = LocatedSpan::new_extra("3'01", info);
-        let _ = value(span).unwrap();
+        let upper = value(LocatedSpan::new_extra("4'XZ01", info)).unwrap().1;
+        let lower = value(LocatedSpan::new_extra("4'xz01", info)).unwrap().1;
+        assert_eq!(upper, lower);
+        assert_eq!(lower, Constant::Value(vec!['1', '0', 'z', 'x']));
+        let upper = value(LocatedSpan::new_extra("3'M", info)).unwrap().1;
+        assert_eq!(upper, Constant::Value(vec!['m', 'm', 'm']));
+    }
+
+    // should fail if the number of bits does not match the width
+    #[test]
+    fn test_value_width_mismatch() {
+        let e = crate::error::parse_complete("3'01", value).unwrap_err();
+        assert_eq!(e.nom_kind(), Some(ErrorKind::Verify));
+        assert_eq!(*e.column(), 1);
+    }
+
+    #[test]
+    fn test_value_malformed_width() {
+        for (input, column) in [("1e3'0", 2), ("'101", 1), ("12ab'0", 3)] {
+            let e = crate::error::parse_complete(input, value).unwrap_err();
+            assert_eq!(e.nom_kind(), Some(ErrorKind::Digit), "{}", input);
+            assert_eq!(*e.column(), column, "{}", input);
+        }
+        let e = crate::parse("module \\a\n  wire $b\n  connect $b 1e3'0\nend\n").unwrap_err();
+        assert_eq!((*e.line(), *e.column()), (3, 15));
+        assert_eq!(e.caret(), "              ^");
+        let e = crate::parse("module \\a\n  wire $b\n  connect $b '101\nend\n").unwrap_err();
+        assert_eq!((*e.line(), *e.column()), (3, 14));
+        // reals and integers are not values
+        assert!(value(Span::new_extra("1.5e3", Default::default())).is_err());
+        assert!(crate::Constant::try_from("1.5e3").is_ok());
+        assert!(crate::Constant::try_from("15").is_ok());
+    }
+
+    #[test]
+    fn test_value_max_width() {
+        for input in ["999999999'0", "99999999999999999999999'0"] {
+            let e = crate::error::parse_complete(input, value).unwrap_err();
+            assert_eq!(e.nom_kind(), Some(ErrorKind::TooLarge));
+        }
+        let e = crate::parse("module \\a\n  connect \\b 999999999'0\nend\n").unwrap_err();
+        assert_eq!((*e.line(), *e.column()), (2, 14));
+
+        let narrow = crate::ParseOptions {
+            max_value_width: 4,
+            ..Default::default()
+        };
+        narrow.scope(|| {
+            assert!(crate::error::parse_complete("4'0", value).is_ok());
+            assert!(crate::error::parse_complete("5'0", value).is_err());
+        });
     }
 }
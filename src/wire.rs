@@ -17,7 +17,11 @@
 use std::collections::HashMap;
 
 use crate::*;
-use nom::{bytes::complete::tag, multi::many0, sequence::terminated, IResult};
+use nom::{
+    bytes::complete::tag,
+    multi::many0,
+    sequence::{preceded, terminated},
+};
 use nom_tracable::tracable_parser;
 
 impl Default for Wire {
@@ -30,11 +34,79 @@ impl Default for Wire {
             inout: false,
             upto: false,
             signed: false,
+            port_id: None,
             attributes: HashMap::new(),
         }
     }
 }
 
+impl Wire {
+    /// The direction of the wire if it is a port, a wire both input and output is inout
+    pub fn direction(&self) -> Option<PortDir> {
+        match (self.input, self.output, self.inout) {
+            (_, _, true) | (true, true, _) => Some(PortDir::Inout),
+            (true, false, false) => Some(PortDir::Input),
+            (false, true, false) => Some(PortDir::Output),
+            (false, false, false) => None,
+        }
+    }
+
+    /// The initial value of the wire, from its `\init` attribute
+    pub fn init_value(&self) -> Option<&Constant> {
+        self.attribute("init")
+    }
+
+    /// Whether the wire is a single bit
+    pub fn is_scalar(&self) -> bool {
+        self.width == 1
+    }
+
+    /// The single bit range over the wire `name` for the bit at `index` in the HDL source
+    ///
+    /// A wire does not know its name, it is given as stored in the module, without sigil.
+    /// `index` counts from the offset of the wire, from its most significant bit if it is `upto`,
+    /// the range counts from the least significant bit at 0. See `Module::resolve_range` for the
+    /// other way around. `None` if the wire has no such bit.
+    ///
+    /// ```
+    /// use rtlicious::{SigSpec, Wire};
+    ///
+    /// let (_, wire) = Wire::from_stmt("wire width 4 offset 8 \\w").unwrap();
+    /// assert_eq!(wire.bit("w", 9), Some(SigSpec::range("w", 1, None)));
+    /// assert_eq!(wire.bit("w", 12), None);
+    /// ```
+    pub fn bit(&self, name: &str, index: usize) -> Option<SigSpec> {
+        let bit = index
+            .checked_sub(self.offset)
+            .filter(|bit| *bit < self.width)?;
+        let bit = if self.upto { self.width - 1 - bit } else { bit };
+        Some(SigSpec::range(name, bit, None))
+    }
+
+    /// Parse a wire from its textual form, its attributes followed by its `wire` statement
+    ///
+    /// The trailing newline is optional.
+    /// `(Id, Wire)` is returned rather than implementing `TryFrom<&str>`, as the id is part of the
+    /// statement and the tuple is not a type of this crate.
+    ///
+    /// ```
+    /// use rtlicious::{Id, Wire};
+    ///
+    /// let (id, wire) = Wire::from_stmt("attribute \\keep 1\nwire width 4 input 1 \\a").unwrap();
+    /// assert_eq!(id, Id::Public("a".to_string()));
+    /// assert_eq!(*wire.width(), 4);
+    /// assert_eq!(*wire.port_id(), Some(1));
+    /// assert!(wire.attributes().contains_key("keep"));
+    /// ```
+    pub fn from_stmt(input: &str) -> Result<(Id, Wire), ParseError> {
+        if input.ends_with('\n') {
+            error::parse_complete(input, wire)
+        } else {
+            error::parse_complete(&format!("{}\n", input), wire)
+        }
+    }
+}
+
 /// `<wire> ::= <attr-stmt>* <wire-stmt>`
 #[tracable_parser]
 pub fn wire(input: Span) -> IResult<Span, (Id, Wire)> {
@@ -51,15 +123,34 @@ pub fn wire_stmt(input: Span) -> IResult<Span, (Id, Wire)> {
     // with sep for each
     let (input, wire_options) = many0(terminated(wire_option, characters::sep))(input)?;
     let (input, id) = identifier::id(input)?;
+    // lenient: `wire \a width 2`
+    let trailing = input;
+    let (input, trailing_options) = if ParseOptions::lenient() {
+        many0(preceded(characters::sep, wire_option))(input)?
+    } else {
+        (input, vec![])
+    };
     let (input, _) = characters::eol(input)?;
+    if !trailing_options.is_empty() {
+        ParseOptions::report(trailing, "wire options after the wire id");
+    }
     let mut wire = Wire::default();
-    for option in wire_options {
+    for option in wire_options.into_iter().chain(trailing_options) {
         match option {
             WireOption::Width(width) => wire.width = width,
             WireOption::Offset(offset) => wire.offset = offset,
-            WireOption::Input => wire.input = true,
-            WireOption::Output => wire.output = true,
-            WireOption::Inout => wire.inout = true,
+            WireOption::Input(port_id) => {
+                wire.input = true;
+                wire.port_id = Some(port_id);
+            }
+            WireOption::Output(port_id) => {
+                wire.output = true;
+                wire.port_id = Some(port_id);
+            }
+            WireOption::Inout(port_id) => {
+                wire.inout = true;
+                wire.port_id = Some(port_id);
+            }
             WireOption::Upto => wire.upto = true,
             WireOption::Signed => wire.signed = true,
         }
@@ -71,9 +162,9 @@ pub fn wire_stmt(input: Span) -> IResult<Span, (Id, Wire)> {
 enum WireOption {
     Width(usize),
     Offset(usize),
-    Input,
-    Output,
-    Inout,
+    Input(usize),
+    Output(usize),
+    Inout(usize),
     Upto,
     Signed,
 }
@@ -115,16 +206,16 @@ fn wire_option(input: Span) -> IResult<Span, WireOption> {
             Ok((input, WireOption::Offset(offset as usize)))
         }
         "input" => {
-            let (input, _input_val) = value::integer(input)?;
-            Ok((input, WireOption::Input))
+            let (input, port_id) = value::integer(input)?;
+            Ok((input, WireOption::Input(port_id as usize)))
         }
         "output" => {
-            let (input, _output) = value::integer(input)?;
-            Ok((input, WireOption::Output))
+            let (input, port_id) = value::integer(input)?;
+            Ok((input, WireOption::Output(port_id as usize)))
         }
         "inout" => {
-            let (input, _inout) = value::integer(input)?;
-            Ok((input, WireOption::Inout))
+            let (input, port_id) = value::integer(input)?;
+            Ok((input, WireOption::Inout(port_id as usize)))
         }
         "upto" => Ok((input, WireOption::Upto)),
         "signed" => Ok((input, WireOption::Signed)),
@@ -152,6 +243,7 @@ mod tests {
                         inout: false,
                         upto: false,
                         signed: false,
+                        port_id: None,
                         attributes: HashMap::new(),
                     },
                 ),
@@ -168,6 +260,7 @@ mod tests {
                         inout: false,
                         upto: false,
                         signed: false,
+                        port_id: None,
                         attributes: HashMap::new(),
                     },
                 ),
@@ -184,6 +277,7 @@ mod tests {
                         inout: false,
                         upto: false,
                         signed: true,
+                        port_id: None,
                         attributes: HashMap::new(),
                     },
                 ),
@@ -200,6 +294,7 @@ mod tests {
                         inout: false,
                         upto: false,
                         signed: false,
+                        port_id: Some(10),
                         attributes: HashMap::new(),
                     },
                 ),
@@ -216,6 +311,7 @@ mod tests {
                         inout: false,
                         upto: false,
                         signed: false,
+                        port_id: Some(5),
                         attributes: HashMap::new(),
                     },
                 ),
@@ -232,6 +328,7 @@ mod tests {
                         inout: true,
                         upto: false,
                         signed: false,
+                        port_id: Some(5),
                         attributes: HashMap::new(),
                     },
                 ),
@@ -248,6 +345,7 @@ mod tests {
                         inout: false,
                         upto: true,
                         signed: false,
+                        port_id: None,
                         attributes: HashMap::new(),
                     },
                 ),
@@ -266,9 +364,9 @@ mod tests {
         let vectors = vec![
             ("width 1", WireOption::Width(1)),
             ("offset 0", WireOption::Offset(0)),
-            ("input 1", WireOption::Input),
-            ("output 1", WireOption::Output),
-            ("inout 1", WireOption::Inout),
+            ("input 1", WireOption::Input(1)),
+            ("output 1", WireOption::Output(1)),
+            ("inout 1", WireOption::Inout(1)),
             ("upto", WireOption::Upto),
             ("signed", WireOption::Signed),
         ];
@@ -278,4 +376,90 @@ mod tests {
             assert_eq!(ret.1, expected);
         }
     }
+
+    #[test]
+    fn test_wire_stmt_trailing_options() {
+        let input = "wire width 4 \\a input 2 signed\n";
+        let span = Span::new_extra(input, Default::default());
+        assert!(wire_stmt(span).is_err());
+        let lenient = ParseOptions {
+            lenient: true,
+            ..Default::default()
+        };
+        let (_, (id, wire)) = lenient.scope(|| wire_stmt(span)).unwrap();
+        assert_eq!(id, Id::Public("a".into()));
+        assert_eq!(wire.width, 4);
+        assert!(wire.input);
+        assert_eq!(wire.port_id, Some(2));
+        assert!(wire.signed);
+
+        let input = "module \\m\n  wire \\a width 2\nend\n";
+        assert!(parse(input).is_err());
+        let design = parse_with(input, &lenient).unwrap();
+        assert_eq!(*design.modules()["m"].wire("a").unwrap().width(), 2);
+    }
+
+    #[test]
+    fn test_wire_stmt_combined_options() {
+        let (id, wire) = Wire::from_stmt("wire width 4 input 1 signed upto \\a").unwrap();
+        assert_eq!(id, Id::Public("a".into()));
+        assert_eq!(
+            wire,
+            Wire {
+                width: 4,
+                input: true,
+                port_id: Some(1),
+                signed: true,
+                upto: true,
+                ..Default::default()
+            }
+        );
+        // options may come in any order, the last one of a kind wins
+        let (_, reordered) =
+            Wire::from_stmt("wire upto signed input 1 width 2 width 4 \\a").unwrap();
+        assert_eq!(reordered, wire);
+        let (_, wire) = Wire::from_stmt("wire inout 3 upto offset 2 signed width 8 $b").unwrap();
+        assert!(wire.inout && wire.upto && wire.signed);
+        assert!(!wire.input && !wire.output);
+        assert_eq!((wire.width, wire.offset, wire.port_id), (8, 2, Some(3)));
+    }
+
+    #[test]
+    fn test_bit() {
+        let (_, scalar) = Wire::from_stmt("wire \\s").unwrap();
+        assert!(scalar.is_scalar());
+        assert_eq!(scalar.bit("s", 0), Some(SigSpec::range("s", 0, None)));
+        assert_eq!(scalar.bit("s", 1), None);
+
+        let design = crate::parse(
+            "module \\m\n  wire width 4 offset 2 \\w\n  wire width 4 offset 2 upto \\u\nend\n",
+        )
+        .unwrap();
+        let module = &design.modules()["m"];
+        let w = module.wire("w").unwrap();
+        assert!(!w.is_scalar());
+        assert_eq!(w.bit("w", 1), None);
+        assert_eq!(w.bit("w", 2), Some(SigSpec::range("w", 0, None)));
+        assert_eq!(w.bit("w", 5), Some(SigSpec::range("w", 3, None)));
+        assert_eq!(w.bit("w", 6), None);
+        let u = module.wire("u").unwrap();
+        assert_eq!(u.bit("u", 2), Some(SigSpec::range("u", 3, None)));
+        assert_eq!(u.bit("u", 5), Some(SigSpec::range("u", 0, None)));
+        for (name, wire) in [("w", w), ("u", u)] {
+            for index in 2..6 {
+                let bit = wire.bit(name, index).unwrap();
+                assert_eq!(module.resolve_range(&bit), Some((index, index)));
+            }
+        }
+    }
+
+    #[test]
+    fn test_direction() {
+        let direction = |s: &str| Wire::from_stmt(s).unwrap().1.direction();
+        assert_eq!(direction("wire \\a"), None);
+        assert_eq!(direction("wire input 1 \\a"), Some(PortDir::Input));
+        assert_eq!(direction("wire output 1 \\a"), Some(PortDir::Output));
+        assert_eq!(direction("wire inout 1 \\a"), Some(PortDir::Inout));
+        assert_eq!(direction("wire input 1 output 1 \\a"), Some(PortDir::Inout));
+    }
 }
@@ -0,0 +1,659 @@
+//! Writes a `Design` back to the RTLIL text representation.
+//!
+//! The layout follows Yosys's `write_rtlil`: attributes precede the item they belong to and
+//! every nesting level is indented by `WriteOptions::indent`. Items stored in maps (modules,
+//! wires, cells, attributes, ...) are written sorted by name so the output is deterministic.
+//! `WriteOptions::yosys_compatible` sorts them the way Yosys does, its output then matches
+//! `write_rtlil` line for line, but for the `# Generated by Yosys` header which is not kept.
+//!
+//! Apart from wires, identifiers are stored without their `$`/`\` sigil, it is recovered as
+//! follows:
+//! * wire references use the sigil of the wire declared in the enclosing module
+//! * attribute and parameter names are public
+//! * cell types naming a module of the design are written like that module's name, other
+//!   cell types are Yosys internal cells, ie. `$and`
+//! * any other name is auto-generated if it contains a `$` or a `\`, public otherwise
+
+use std::{
+    collections::HashMap,
+    fmt::{self, Write},
+    io,
+};
+
+use crate::*;
+
+impl Default for WriteOptions {
+    fn default() -> Self {
+        Self {
+            indent: Indent::Spaces(2),
+            yosys_order: false,
+        }
+    }
+}
+
+impl WriteOptions {
+    /// The options writing a design the way Yosys's `write_rtlil` does
+    pub fn yosys_compatible() -> Self {
+        Self {
+            indent: Indent::Spaces(2),
+            yosys_order: true,
+        }
+    }
+}
+
+impl Design {
+    /// Write the design as RTLIL text using the default `WriteOptions`
+    pub fn to_rtlil_string(&self) -> String {
+        self.to_rtlil_string_with(&WriteOptions::default())
+    }
+
+    /// Write the design as RTLIL text
+    pub fn to_rtlil_string_with(&self, options: &WriteOptions) -> String {
+        let mut writer = Writer {
+            design: self,
+            options,
+            out: String::new(),
+        };
+        writer.design();
+        writer.out
+    }
+
+    /// Write the design as RTLIL text to `w`
+    pub fn write_rtlil<W: io::Write>(&self, mut w: W, options: &WriteOptions) -> io::Result<()> {
+        w.write_all(self.to_rtlil_string_with(options).as_bytes())
+    }
+
+    /// Write only the module `name` as RTLIL text, as it is written in the whole design, using
+    /// the default `WriteOptions`
+    ///
+    /// `None` if there is no such module.
+    pub fn module_to_rtlil_string(&self, name: &str) -> Option<String> {
+        self.module_to_rtlil_string_with(name, &WriteOptions::default())
+    }
+
+    /// Write only the module `name` as RTLIL text, as it is written in the whole design
+    ///
+    /// `None` if there is no such module.
+    pub fn module_to_rtlil_string_with(
+        &self,
+        name: &str,
+        options: &WriteOptions,
+    ) -> Option<String> {
+        let module = self.modules.get(name)?;
+        Some(module.to_rtlil_string(self, name, options))
+    }
+}
+
+impl Module {
+    /// Write the module as RTLIL text, named `name`, as it is written in `design`
+    ///
+    /// The cell types naming a module of `design` are written with a `\`, the others as Yosys
+    /// internal cells.
+    ///
+    /// ```
+    /// let src = "module \\a\nend\nmodule \\b\n  cell \\a \\i\n  end\nend\n";
+    /// let design = rtlicious::parse(src).unwrap();
+    /// let options = rtlicious::WriteOptions::default();
+    /// let b = design.modules()["b"].to_rtlil_string(&design, "b", &options);
+    /// assert_eq!(b, "module \\b\n  cell \\a \\i\n  end\nend\n");
+    /// ```
+    pub fn to_rtlil_string(&self, design: &Design, name: &str, options: &WriteOptions) -> String {
+        let mut writer = Writer {
+            design,
+            options,
+            out: String::new(),
+        };
+        writer.module(name, self);
+        writer.out
+    }
+}
+
+impl fmt::Display for Id {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Id::Public(id) => write!(f, "\\{}", id),
+            Id::Autogen(id) => write!(f, "${}", id),
+        }
+    }
+}
+
+impl fmt::Display for Constant {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            // bits are stored LSB first
+            Constant::Value(bits) => {
+                write!(f, "{}'", bits.len())?;
+                bits.iter().rev().try_for_each(|bit| f.write_char(*bit))
+            }
+            Constant::Fill(bit, width) => write!(f, "{}'{}", width, bit),
+            Constant::Integer(integer) => write!(f, "{}", integer),
+            Constant::Real(real) => f.write_str(real),
+            Constant::String(string) => {
+                f.write_char('"')?;
+                for c in string.chars() {
+                    match c {
+                        '\n' => f.write_str("\\n")?,
+                        '\t' => f.write_str("\\t")?,
+                        '"' | '\\' => write!(f, "\\{}", c)?,
+                        c if (c as u32) < 32 => write!(f, "\\{:03o}", c as u32)?,
+                        c => f.write_char(c)?,
+                    }
+                }
+                f.write_char('"')
+            }
+        }
+    }
+}
+
+/// Recover the sigil of a name stored without it
+fn id(name: &str) -> Id {
+    if name.contains(['$', '\\']) {
+        Id::Autogen(name.to_string())
+    } else {
+        Id::Public(name.to_string())
+    }
+}
+
+/// Iterate a map sorted by key
+fn sorted<K: Ord, V>(map: &HashMap<K, V>) -> Vec<(&K, &V)> {
+    let mut items: Vec<_> = map.iter().collect();
+    items.sort_by(|a, b| a.0.cmp(b.0));
+    items
+}
+
+struct Writer<'a> {
+    design: &'a Design,
+    options: &'a WriteOptions,
+    out: String,
+}
+
+impl<'a> Writer<'a> {
+    /// Write one line at the given nesting level
+    fn line(&mut self, level: usize, args: fmt::Arguments) {
+        for _ in 0..level {
+            match self.options.indent {
+                Indent::Spaces(n) => self.out.extend(std::iter::repeat_n(' ', n)),
+                Indent::Tab => self.out.push('\t'),
+            }
+        }
+        // writing to a String cannot fail
+        let _ = self.out.write_fmt(args);
+        self.out.push('\n');
+    }
+
+    fn design(&mut self) {
+        let design = self.design;
+        if let Some(autoidx) = design.autoidx {
+            self.line(0, format_args!("autoidx {}", autoidx));
+        }
+        for (name, module) in sorted(&design.modules) {
+            self.module(name, module);
+        }
+    }
+
+    fn attributes(&mut self, level: usize, attributes: &HashMap<String, Constant>) {
+        for (name, value) in sorted(attributes) {
+            self.line(level, format_args!("attribute \\{} {}", name, value));
+        }
+    }
+
+    fn module(&mut self, name: &str, module: &'a Module) {
+        self.attributes(0, &module.attributes);
+        self.line(0, format_args!("module {}", id(name)));
+        let mut items: Vec<ModuleItem> = module.items().collect();
+        if self.options.yosys_order {
+            // a stable sort, the parameters and connections keep their order
+            items.sort_by_cached_key(|item| match item {
+                ModuleItem::Param(..) => (0, None),
+                ModuleItem::Wire(id, _) => (1, Some(id.to_string())),
+                ModuleItem::Memory(name, _) => (2, Some(id(name).to_string())),
+                ModuleItem::Cell(name, _) => (3, Some(id(name).to_string())),
+                ModuleItem::Process(name, _) => (4, Some(id(name).to_string())),
+                ModuleItem::Connection(..) => (5, None),
+            });
+        }
+        for item in items {
+            match item {
+                ModuleItem::Param(name, Some(value)) => {
+                    self.line(1, format_args!("parameter \\{} {}", name, value))
+                }
+                ModuleItem::Param(name, None) => self.line(1, format_args!("parameter \\{}", name)),
+                ModuleItem::Wire(id, wire) => self.wire(1, id, wire),
+                ModuleItem::Memory(name, memory) => self.memory(1, name, memory),
+                ModuleItem::Cell(name, cell) => self.cell(1, module, name, cell),
+                ModuleItem::Process(name, process) => self.process(1, module, name, process),
+                ModuleItem::Connection(dest, src) => {
+                    let (dest, src) = (sigspec(module, dest), sigspec(module, src));
+                    self.line(1, format_args!("connect {} {}", dest, src));
+                }
+            }
+        }
+        self.line(0, format_args!("end"));
+    }
+
+    fn wire(&mut self, level: usize, id: &Id, wire: &Wire) {
+        self.attributes(level, &wire.attributes);
+        let mut options = String::new();
+        if wire.width != 1 {
+            let _ = write!(options, "width {} ", wire.width);
+        }
+        if wire.upto {
+            options.push_str("upto ");
+        }
+        if wire.offset != 0 {
+            let _ = write!(options, "offset {} ", wire.offset);
+        }
+        let port_id = wire.port_id.unwrap_or(0);
+        if wire.input {
+            let _ = write!(options, "input {} ", port_id);
+        }
+        if wire.output {
+            let _ = write!(options, "output {} ", port_id);
+        }
+        if wire.inout {
+            let _ = write!(options, "inout {} ", port_id);
+        }
+        if wire.signed {
+            options.push_str("signed ");
+        }
+        self.line(level, format_args!("wire {}{}", options, id));
+    }
+
+    fn memory(&mut self, level: usize, name: &str, memory: &Memory) {
+        self.attributes(level, &memory.attributes);
+        let mut options = String::new();
+        if memory.width != 0 {
+            let _ = write!(options, "width {} ", memory.width);
+        }
+        if memory.size != 0 {
+            let _ = write!(options, "size {} ", memory.size);
+        }
+        if memory.offset != 0 {
+            let _ = write!(options, "offset {} ", memory.offset);
+        }
+        self.line(level, format_args!("memory {}{}", options, id(name)));
+    }
+
+    fn cell(&mut self, level: usize, module: &Module, name: &str, cell: &Cell) {
+        self.attributes(level, &cell.attributes);
+        let cell_type = if self.design.modules.contains_key(&cell.cell_type) {
+            id(&cell.cell_type)
+        } else {
+            Id::Autogen(cell.cell_type.clone())
+        };
+        self.line(level, format_args!("cell {} {}", cell_type, id(name)));
+        for (name, value) in sorted(&cell.parameters) {
+            let kind = match cell.parameter_kinds.get(name) {
+                Some(ParameterKind::Signed) => "signed ",
+                Some(ParameterKind::Real) => "real ",
+                None => "",
+            };
+            self.line(
+                level + 1,
+                format_args!("parameter {}\\{} {}", kind, name, value),
+            );
+        }
+        for (port, signal) in sorted(&cell.connections) {
+            let signal = sigspec(module, signal);
+            self.line(level + 1, format_args!("connect \\{} {}", port, signal));
+        }
+        self.line(level, format_args!("end"));
+    }
+
+    fn process(&mut self, level: usize, module: &Module, name: &str, process: &Process) {
+        self.attributes(level, &process.attributes);
+        self.line(level, format_args!("process {}", id(name)));
+        for (dest, src) in &process.assignments {
+            let (dest, src) = (sigspec(module, dest), sigspec(module, src));
+            self.line(level + 1, format_args!("assign {} {}", dest, src));
+        }
+        for switch in &process.switches {
+            self.switch(level + 1, module, switch);
+        }
+        for sync in &process.syncs {
+            self.sync(level + 1, module, sync);
+        }
+        self.line(level, format_args!("end"));
+    }
+
+    fn switch(&mut self, level: usize, module: &Module, switch: &Switch) {
+        self.attributes(level, &switch.attributes);
+        let on = sigspec(module, &switch.switch_on_sigspec);
+        self.line(level, format_args!("switch {}", on));
+        for case in &switch.cases {
+            self.attributes(level + 1, &case.attributes);
+            let compare = case
+                .compare_against
+                .iter()
+                .flatten()
+                .map(|s| sigspec(module, s))
+                .collect::<Vec<_>>()
+                .join(" , ");
+            self.line(level + 1, format_args!("case {}", compare));
+            for body in &case.case_bodies {
+                match body {
+                    CaseBody::Switch(switch) => self.switch(level + 2, module, switch),
+                    CaseBody::Assign((dest, src)) => {
+                        let (dest, src) = (sigspec(module, dest), sigspec(module, src));
+                        self.line(level + 2, format_args!("assign {} {}", dest, src));
+                    }
+                }
+            }
+        }
+        self.line(level, format_args!("end"));
+    }
+
+    fn sync(&mut self, level: usize, module: &Module, sync: &Sync) {
+        match &sync.sync_event {
+            SyncOn::Global => self.line(level, format_args!("sync global")),
+            SyncOn::Init => self.line(level, format_args!("sync init")),
+            SyncOn::Always => self.line(level, format_args!("sync always")),
+            SyncOn::Signal(sync_type, signal) => {
+                let sync_type = match sync_type {
+                    SignalSync::Low => "low",
+                    SignalSync::High => "high",
+                    SignalSync::Posedge => "posedge",
+                    SignalSync::Negedge => "negedge",
+                    SignalSync::Edge => "edge",
+                };
+                let signal = sigspec(module, signal);
+                self.line(level, format_args!("sync {} {}", sync_type, signal));
+            }
+        }
+        for update in &sync.updates {
+            self.attributes(level + 1, &update.attributes);
+            let (dest, src) = (sigspec(module, &update.dest), sigspec(module, &update.src));
+            self.line(level + 1, format_args!("update {} {}", dest, src));
+        }
+        for (memid, memwr) in sorted(&sync.memwrs) {
+            self.attributes(level + 1, &memwr.attributes);
+            self.line(
+                level + 1,
+                format_args!(
+                    "memwr {} {} {} {} {}",
+                    id(memid),
+                    sigspec(module, &memwr.address),
+                    sigspec(module, &memwr.data),
+                    sigspec(module, &memwr.enable),
+                    sigspec(module, &memwr.priority_mask),
+                ),
+            );
+        }
+    }
+}
+
+/// Write a sigspec, resolving wire sigils against the wires of `module`
+fn sigspec(module: &Module, signal: &SigSpec) -> String {
+    match signal {
+        SigSpec::Constant(constant) => constant.to_string(),
+        SigSpec::WireId(name) => {
+            let autogen = Id::Autogen(name.clone());
+            let public = Id::Public(name.clone());
+            if module.wires.contains_key(&autogen) {
+                autogen.to_string()
+            } else if module.wires.contains_key(&public) {
+                public.to_string()
+            } else {
+                id(name).to_string()
+            }
+        }
+        SigSpec::Range(inner, start, None) => format!("{} [{}]", sigspec(module, inner), start),
+        SigSpec::Range(inner, start, Some(end)) => {
+            format!("{} [{}:{}]", sigspec(module, inner), start, end)
+        }
+        SigSpec::Concat(parts) => {
+            let mut out = String::from("{ ");
+            for part in parts {
+                out.push_str(&sigspec(module, part));
+                out.push(' ');
+            }
+            out.push('}');
+            out
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+    use pretty_assertions::assert_eq;
+
+    const COMB_NOT1: &str = indoc! {r#"
+        autoidx 3
+        attribute \src "vectors/comb_not1.v:2.1-9.10"
+        attribute \top 1
+        module \comb_not1
+          attribute \src "vectors/comb_not1.v:6.5-8.8"
+          wire $0\b[0:0]
+          attribute \src "vectors/comb_not1.v:3.11-3.12"
+          wire input 1 \a
+          attribute \src "vectors/comb_not1.v:4.12-4.13"
+          wire output 2 \b
+          attribute \src "vectors/comb_not1.v:7.13-7.15"
+          wire $logic_not$vectors/comb_not1.v:7$2_Y
+          attribute \src "vectors/comb_not1.v:7.13-7.15"
+          cell $logic_not $logic_not$vectors/comb_not1.v:7$2
+            parameter \A_SIGNED 0
+            parameter \A_WIDTH 1
+            parameter \Y_WIDTH 1
+            connect \A \a
+            connect \Y $logic_not$vectors/comb_not1.v:7$2_Y
+          end
+          attribute \src "vectors/comb_not1.v:6.5-8.8"
+          process $proc$vectors/comb_not1.v:6$1
+            assign { } { }
+            assign $0\b[0:0] $logic_not$vectors/comb_not1.v:7$2_Y
+            attribute \src "vectors/comb_not1.v:7.7-7.9"
+            switch \a
+              case 1'1 , 1'0
+                assign $0\b[0:0] \a [0]
+              case 
+            end
+            sync always
+              update \b $0\b[0:0]
+          end
+          connect $0\b[0:0] { \a [0:0] 1'x }
+        end
+    "#};
+
+    #[test]
+    fn test_write_design() {
+        let design = Design::new_from_str(COMB_NOT1).unwrap();
+        assert_eq!(design.to_rtlil_string(), COMB_NOT1);
+    }
+
+    #[test]
+    fn test_write_indent() {
+        let design = Design::new_from_str(COMB_NOT1).unwrap();
+        let options = WriteOptions {
+            indent: Indent::Spaces(4),
+            ..Default::default()
+        };
+        let out = design.to_rtlil_string_with(&options);
+        let lines: Vec<&str> = out.lines().collect();
+        assert!(lines.contains(&"    wire input 1 \\a"));
+        assert!(lines.contains(&"        parameter \\A_SIGNED 0"));
+        assert!(lines.contains(&"            case 1'1 , 1'0"));
+        assert!(lines.contains(&"                assign $0\\b[0:0] \\a [0]"));
+        assert_eq!(Design::new_from_str(&out).unwrap(), design);
+
+        let options = WriteOptions {
+            indent: Indent::Tab,
+            ..Default::default()
+        };
+        let out = design.to_rtlil_string_with(&options);
+        assert!(out.lines().any(|l| l == "\t\tconnect \\A \\a"));
+        assert_eq!(Design::new_from_str(&out).unwrap(), design);
+    }
+
+    #[test]
+    fn test_write_yosys_compatible() {
+        let golden = include_str!("../tests/fixtures/counter.il");
+        let (header, expected) = golden.split_once('\n').unwrap();
+        assert!(header.starts_with("# Generated by Yosys"));
+        let design = Design::new_from_str(golden).unwrap();
+        let options = WriteOptions::yosys_compatible();
+        assert_eq!(design.to_rtlil_string_with(&options), expected);
+        // by bare name, the public \acc comes before $add$counter.v:9$2_Y
+        assert_ne!(design.to_rtlil_string(), expected);
+    }
+
+    #[test]
+    fn test_write_combined_wire_options() {
+        let input = indoc! {r#"
+            module \a
+              wire width 4 input 1 signed upto \a
+              wire inout 3 upto offset 2 signed width 8 $b
+              wire signed output 2 width 3 \c
+            end
+        "#};
+        let design = Design::new_from_str(input).unwrap();
+        // written in the order of Yosys: width, upto, offset, direction, signed
+        let expected = indoc! {r#"
+            module \a
+              wire width 4 upto input 1 signed \a
+              wire width 8 upto offset 2 inout 3 signed $b
+              wire width 3 output 2 signed \c
+            end
+        "#};
+        let written = design.to_rtlil_string();
+        assert_eq!(written, expected);
+        assert_eq!(Design::new_from_str(&written).unwrap(), design);
+    }
+
+    #[test]
+    fn test_write_parameter_kinds() {
+        let input = indoc! {r#"
+            module \a
+              cell $shr \s
+                parameter signed \A_SIGNED 1
+                parameter \A_WIDTH 8
+                parameter real \DELAY "1.5"
+              end
+            end
+        "#};
+        let design = Design::new_from_str(input).unwrap();
+        let cell = &design.modules()["a"].cells()["s"];
+        assert_eq!(
+            cell.parameter_kinds().get("A_SIGNED"),
+            Some(&ParameterKind::Signed)
+        );
+        assert_eq!(cell.parameter_kinds().get("A_WIDTH"), None);
+        assert_eq!(design.to_rtlil_string(), input);
+    }
+
+    #[test]
+    fn test_write_offset_range() {
+        // range indices are written as parsed, the offset does not shift them
+        let input = indoc! {r#"
+            module \a
+              wire width 8 offset 4 \w
+              wire width 2 \y
+              connect \y \w [5:4]
+            end
+        "#};
+        let design = Design::new_from_str(input).unwrap();
+        let module = &design.modules()["a"];
+        let range = &module.connections()[0].1;
+        assert_eq!(*range, SigSpec::range("w", 5, Some(4)));
+        assert_eq!(module.resolve_range(range), Some((9, 8)));
+        assert_eq!(design.to_rtlil_string(), input);
+    }
+
+    #[test]
+    fn test_write_empty_concat() {
+        let input = indoc! {r#"
+            module \a
+              wire $b
+              connect { } { }
+              connect $b { { } $b }
+            end
+        "#};
+        let design = Design::new_from_str(input).unwrap();
+        assert_eq!(design.to_rtlil_string(), input);
+    }
+
+    #[test]
+    fn test_write_exact_values() {
+        let input = indoc! {r#"
+            module \a
+              wire width 4 $b
+              connect $b 4'1
+              connect $b 4'x01z
+            end
+        "#};
+        let options = ParseOptions {
+            exact_values: true,
+            ..Default::default()
+        };
+        let design = parse_with(input, &options).unwrap();
+        let (_, src) = &design.modules()["a"].connections()[0];
+        assert_eq!(*src, SigSpec::Constant(Constant::Fill('1', 4)));
+        assert_eq!(design.to_rtlil_string(), input);
+        assert_eq!(
+            parse_with(design.to_rtlil_string(), &options).unwrap(),
+            design
+        );
+        // the same bits either way
+        let mut expanded = parse(input).unwrap();
+        assert!(expanded.to_rtlil_string().contains("connect $b 4'1111\n"));
+        let mut design = design;
+        design.canonicalize();
+        expanded.canonicalize();
+        assert_eq!(design, expanded);
+    }
+
+    #[test]
+    fn test_write_module() {
+        let input = indoc! {r#"
+            module \sub
+              wire input 1 \a
+            end
+            module \top
+              wire \x
+              cell \sub \s
+                connect \a \x
+              end
+            end
+        "#};
+        let design = Design::new_from_str(input).unwrap();
+        let top = design.module_to_rtlil_string("top").unwrap();
+        assert_eq!(top, input.split_once("end\n").unwrap().1);
+        assert_eq!(design.module_to_rtlil_string("missing"), None);
+        let options = WriteOptions {
+            indent: Indent::Tab,
+            ..Default::default()
+        };
+        let tabs = design.modules()["top"].to_rtlil_string(&design, "top", &options);
+        assert_eq!(tabs, top.replace("  ", "\t"));
+        assert_eq!(
+            design.module_to_rtlil_string_with("top", &options),
+            Some(tabs.clone())
+        );
+        // the instance of \sub keeps its sigil and parses back the same
+        assert!(tabs.contains("cell \\sub \\s\n"));
+        let reparsed = Design::new_from_str(&tabs).unwrap();
+        assert_eq!(reparsed.modules().len(), 1);
+        assert_eq!(reparsed.modules()["top"], design.modules()["top"]);
+    }
+
+    #[test]
+    fn test_write_constant() {
+        let vectors = [
+            (Constant::Value(vec!['1', '0', 'x']), "3'x01"),
+            (Constant::Value(vec![]), "0'"),
+            (Constant::Integer(-12), "-12"),
+            (
+                Constant::String("a \"b\"\n\\".to_string()),
+                r#""a \"b\"\n\\""#,
+            ),
+            (Constant::String("\x01".to_string()), r#""\001""#),
+        ];
+        for (constant, expected) in vectors {
+            assert_eq!(constant.to_string(), expected);
+            let span = Span::new_extra(expected, Default::default());
+            assert_eq!(constant::constant(span).unwrap().1, constant);
+        }
+    }
+}